@@ -1,5 +1,8 @@
 #[cfg(not(target_family = "wasm"))]
 #[doc(cfg(not(target_family = "wasm")))]
+pub use cancellation::*;
+#[cfg(not(target_family = "wasm"))]
+#[doc(cfg(not(target_family = "wasm")))]
 pub use type_aliases::*;
 
 /// wasm bindings for tauri's provided js functions
@@ -7,6 +10,22 @@ pub use type_aliases::*;
 #[doc(cfg(target_family = "wasm"))]
 pub mod bindings;
 
+/// invoking multiple commands in a single round trip
+pub mod batch;
+
+/// sharing and caching of command results in the frontend
+#[cfg(any(target_family = "wasm", doc))]
+#[doc(cfg(target_family = "wasm"))]
+pub mod cache;
+
+/// long-running commands, which are tracked as jobs on the host
+pub mod job;
+
+/// cancellation of running commands, triggered by the frontend
+#[cfg(not(target_family = "wasm"))]
+#[doc(cfg(not(target_family = "wasm")))]
+mod cancellation;
+
 #[cfg(not(target_family = "wasm"))]
 #[doc(cfg(not(target_family = "wasm")))]
 mod type_aliases;
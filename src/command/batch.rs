@@ -0,0 +1,22 @@
+#[cfg(any(target_family = "wasm", doc))]
+#[doc(cfg(target_family = "wasm"))]
+pub use collector::*;
+#[cfg(not(target_family = "wasm"))]
+#[doc(cfg(not(target_family = "wasm")))]
+pub use dispatch::*;
+
+/// collecting of command invocations in the frontend
+#[cfg(any(target_family = "wasm", doc))]
+#[doc(cfg(target_family = "wasm"))]
+mod collector;
+
+/// executing of collected command invocations on the host
+#[cfg(not(target_family = "wasm"))]
+#[doc(cfg(not(target_family = "wasm")))]
+mod dispatch;
+
+/// Name of the command, which executes a batch of commands on the host
+///
+/// The command is handled by every handler generated through `collect_commands` or
+/// `combine_handlers`, so it doesn't need to be registered manually.
+pub const BATCH_COMMAND: &str = "__tauri_interop_batch";
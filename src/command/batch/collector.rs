@@ -0,0 +1,228 @@
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+
+use futures::channel::oneshot;
+use futures::future::{self, Either};
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::JsValue;
+
+use super::BATCH_COMMAND;
+use crate::command::bindings::invoke;
+
+type Response = Result<JsValue, JsValue>;
+
+struct PendingCall {
+    command: String,
+    args: JsValue,
+    sender: oneshot::Sender<Response>,
+}
+
+/// The calls of a batch, which weren't sent yet
+#[derive(Default)]
+struct Pending {
+    calls: Vec<PendingCall>,
+    /// The waker of the [Flush] sending the calls
+    flush: Option<Waker>,
+}
+
+type Queue = Rc<RefCell<Pending>>;
+
+thread_local! {
+    static CURRENT_BATCH: RefCell<Option<Queue>> = const { RefCell::new(None) };
+}
+
+/// Queues the invocation into the batch that is currently polled
+///
+/// Returns the arguments, when the invocation isn't part of a batch.
+pub(crate) fn enqueue(
+    command: &str,
+    args: JsValue,
+) -> Result<oneshot::Receiver<Response>, JsValue> {
+    CURRENT_BATCH.with_borrow(|batch| {
+        let Some(queue) = batch else { return Err(args) };
+
+        let (sender, receiver) = oneshot::channel();
+        let mut pending = queue.borrow_mut();
+        pending.calls.push(PendingCall {
+            command: command.into(),
+            args,
+            sender,
+        });
+        if let Some(flush) = pending.flush.take() {
+            flush.wake();
+        }
+
+        Ok(receiver)
+    })
+}
+
+/// Collector of the commands, that are invoked as part of a [batch]
+#[derive(Default)]
+pub struct Batch {
+    queue: Queue,
+}
+
+impl Batch {
+    /// Adds the future of a command binding to the batch
+    ///
+    /// Every command that is invoked while polling the future is sent with the batch
+    /// instead of being invoked separately.
+    pub fn call<F: Future>(&self, future: F) -> BatchCall<F> {
+        BatchCall {
+            future: Box::pin(future),
+            queue: self.queue.clone(),
+        }
+    }
+}
+
+/// Future of a command binding, which is part of a [batch]
+pub struct BatchCall<F: Future> {
+    future: Pin<Box<F>>,
+    queue: Queue,
+}
+
+impl<F: Future> Future for BatchCall<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let previous = CURRENT_BATCH.replace(Some(self.queue.clone()));
+        let poll = self.future.as_mut().poll(cx);
+        CURRENT_BATCH.set(previous);
+
+        poll
+    }
+}
+
+/// The calls returned by the closure of [batch]
+///
+/// Implemented for tuples of up to twelve [BatchCall] and for a [Vec] of [BatchCall].
+pub trait BatchCalls {
+    /// The outputs of the calls, in the same shape as the calls
+    type Output;
+
+    /// Returns a future which resolves when every call completed
+    fn join(self) -> impl Future<Output = Self::Output>;
+}
+
+impl<F: Future> BatchCalls for Vec<BatchCall<F>> {
+    type Output = Vec<F::Output>;
+
+    fn join(self) -> impl Future<Output = Self::Output> {
+        future::join_all(self)
+    }
+}
+
+macro_rules! impl_batch_calls {
+    ($($future:ident $call:ident),+) => {
+        impl<$($future: Future),+> BatchCalls for ($(BatchCall<$future>,)+) {
+            type Output = ($($future::Output,)+);
+
+            fn join(self) -> impl Future<Output = Self::Output> {
+                let ($($call,)+) = self;
+                async move { futures::join!($($call),+) }
+            }
+        }
+    };
+}
+
+impl_batch_calls!(A a);
+impl_batch_calls!(A a, B b);
+impl_batch_calls!(A a, B b, C c);
+impl_batch_calls!(A a, B b, C c, D d);
+impl_batch_calls!(A a, B b, C c, D d, E e);
+impl_batch_calls!(A a, B b, C c, D d, E e, F f);
+impl_batch_calls!(A a, B b, C c, D d, E e, F f, G g);
+impl_batch_calls!(A a, B b, C c, D d, E e, F f, G g, H h);
+impl_batch_calls!(A a, B b, C c, D d, E e, F f, G g, H h, I i);
+impl_batch_calls!(A a, B b, C c, D d, E e, F f, G g, H h, I i, J j);
+impl_batch_calls!(A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k);
+impl_batch_calls!(A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k, L l);
+
+/// Sends the queued invocations of a batch, and is woken when an invocation is queued
+struct Flush(Queue);
+
+impl Future for Flush {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut pending = self.0.borrow_mut();
+        let calls = std::mem::take(&mut pending.calls);
+        match &mut pending.flush {
+            Some(waker) => waker.clone_from(cx.waker()),
+            None => pending.flush = Some(cx.waker().clone()),
+        }
+        drop(pending);
+
+        if !calls.is_empty() {
+            wasm_bindgen_futures::spawn_local(dispatch(calls));
+        }
+
+        Poll::Pending
+    }
+}
+
+async fn dispatch(calls: Vec<PendingCall>) {
+    log::trace!("Sending batch of {} commands", calls.len());
+
+    let requests = calls
+        .iter()
+        .map(|call| {
+            let request = Object::new();
+            let _ = Reflect::set(&request, &"command".into(), &call.command.as_str().into());
+            let _ = Reflect::set(&request, &"args".into(), &call.args);
+            request
+        })
+        .collect::<Array>();
+    let args = Object::new();
+    let _ = Reflect::set(&args, &"calls".into(), &requests);
+
+    match invoke(BATCH_COMMAND, args.into()).await {
+        Ok(responses) => {
+            let responses = Array::from(&responses);
+            for (call, response) in calls.into_iter().zip(responses.iter()) {
+                let response = if Reflect::has(&response, &"Ok".into()).unwrap_or_default() {
+                    Ok(Reflect::get(&response, &"Ok".into()).unwrap_or_default())
+                } else {
+                    Err(Reflect::get(&response, &"Err".into()).unwrap_or_default())
+                };
+
+                let _ = call.sender.send(response);
+            }
+        }
+        Err(why) => calls.into_iter().for_each(|call| {
+            let _ = call.sender.send(Err(why.clone()));
+        }),
+    }
+}
+
+/// Invokes every command of the calls in a single round trip to the host
+///
+/// The closure receives a [Batch], which is used to add the futures of command bindings
+/// to the batch. Every call keeps its own return type and error handling, the outputs
+/// are returned in the same shape as the calls. Commands invoked after the first await
+/// point of a call are sent with a following batch. Bindings of synchronous commands
+/// (without return type) are added by calling them inside of a call, e.g.
+/// `batch.call(async { cmd::trigger() })`.
+///
+/// ### Example
+///
+/// ```ignore
+/// async fn load_dashboard() {
+///     let (name, stats) = tauri_interop::batch(|batch| {
+///         (batch.call(cmd::user_name()), batch.call(cmd::statistics(7)))
+///     })
+///     .await;
+/// }
+/// ```
+pub async fn batch<C: BatchCalls>(calls: impl FnOnce(&Batch) -> C) -> C::Output {
+    let batch = Batch::default();
+    let calls = pin!(calls(&batch).join());
+
+    match future::select(calls, Flush(batch.queue)).await {
+        Either::Left((output, _)) => output,
+        Either::Right(_) => unreachable!("flushing the batch never completes"),
+    }
+}
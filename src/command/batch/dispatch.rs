@@ -0,0 +1,213 @@
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+
+use futures::future;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::ipc::{CommandArg, CommandItem, Invoke, InvokeBody, InvokeError, InvokeMessage};
+use tauri::Runtime;
+
+use super::BATCH_COMMAND;
+use crate::command::CancellationToken;
+
+/// Response of a batched command, either as `Ok` or `Err` value
+pub type BatchResponse = Pin<Box<dyn Future<Output = Result<Value, Value>> + Send>>;
+
+#[derive(Deserialize)]
+struct BatchArgs {
+    calls: Vec<BatchedCall>,
+}
+
+#[derive(Deserialize)]
+struct BatchedCall {
+    command: String,
+    args: Value,
+}
+
+/// Wraps the given invoke handler, so that it additionally handles the [BATCH_COMMAND]
+///
+/// The commands of a batch are executed by the `dispatcher`, which returns `None` for a
+/// command it doesn't know. Used by the handler functions generated through
+/// `collect_commands` and `combine_handlers`, which dispatch to the same commands that are
+/// registered with the handler.
+pub fn with_batching<R: Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+    dispatcher: impl Fn(&InvokeMessage<R>, &str, Value) -> Option<BatchResponse> + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        if invoke.message.command() != BATCH_COMMAND {
+            return handler(invoke);
+        }
+
+        dispatch(invoke, &dispatcher);
+        true
+    }
+}
+
+/// Executes every command of a batch and resolves the batch with all responses
+///
+/// The arguments provided by tauri (e.g. `State` or `Webview`) are resolved from the
+/// invocation of the batch itself, the remaining arguments from the batched call. Like
+/// separately invoked commands, synchronous commands are executed immediately while async
+/// commands are awaited together. The responses are returned in the order of the calls.
+fn dispatch<R: Runtime>(
+    invoke: Invoke<R>,
+    dispatcher: &impl Fn(&InvokeMessage<R>, &str, Value) -> Option<BatchResponse>,
+) {
+    let Invoke {
+        message, resolver, ..
+    } = invoke;
+
+    let batch = match message.payload() {
+        InvokeBody::Json(payload) => BatchArgs::deserialize(payload),
+        InvokeBody::Raw(_) => return resolver.reject("a batch requires a json payload"),
+    };
+    let calls = match batch {
+        Ok(batch) => batch.calls,
+        Err(why) => return resolver.reject(format!("invalid batch: {why}")),
+    };
+
+    log::trace!("Dispatching batch of {} commands", calls.len());
+    let responses = calls
+        .into_iter()
+        .map(|call| {
+            if call.command == BATCH_COMMAND {
+                return rejected("batches can't be nested".into());
+            }
+
+            dispatcher(&message, &call.command, call.args)
+                .unwrap_or_else(|| rejected(format!("command {} not found", call.command)))
+        })
+        .collect::<Vec<_>>();
+
+    resolver.respond_async(async move { Ok::<_, InvokeError>(future::join_all(responses).await) });
+}
+
+fn rejected(why: String) -> BatchResponse {
+    Box::pin(future::ready(Err(Value::from(why))))
+}
+
+/// Argument of a batched command
+///
+/// Used by the dispatchers generated through `tauri_interop::command`, which take the type
+/// of the argument from the command. The argument is deserialized from the batched call
+/// via [FromBatchedCall], otherwise it is provided by tauri via [FromBatchInvoke].
+#[doc(hidden)]
+pub struct BatchArg<'a, R: Runtime, T> {
+    message: &'a InvokeMessage<R>,
+    args: &'a Value,
+    command: &'static str,
+    key: &'static str,
+    argument: PhantomData<T>,
+}
+
+impl<'a, R: Runtime, T> BatchArg<'a, R, T> {
+    pub fn new(
+        message: &'a InvokeMessage<R>,
+        args: &'a Value,
+        command: &'static str,
+        key: &'static str,
+        argument: PhantomData<T>,
+    ) -> Self {
+        Self {
+            message,
+            args,
+            command,
+            key,
+            argument,
+        }
+    }
+}
+
+#[doc(hidden)]
+pub trait FromBatchedCall<T> {
+    fn resolve(&self) -> Result<T, Value>;
+}
+
+impl<'a, R: Runtime, T: Deserialize<'a>> FromBatchedCall<T> for BatchArg<'a, R, T> {
+    fn resolve(&self) -> Result<T, Value> {
+        let value = self.args.get(self.key).unwrap_or(&Value::Null);
+        T::deserialize(value).map_err(|why| {
+            let why = tauri::Error::InvalidArgs(self.command, self.key, why);
+            Value::from(why.to_string())
+        })
+    }
+}
+
+#[doc(hidden)]
+pub trait FromBatchInvoke<T> {
+    fn resolve(&self) -> Result<T, Value>;
+}
+
+impl<'a, R: Runtime, T: CommandArg<'a, R>> FromBatchInvoke<T> for &BatchArg<'a, R, T> {
+    fn resolve(&self) -> Result<T, Value> {
+        T::from_command(CommandItem {
+            plugin: None,
+            name: self.command,
+            key: self.key,
+            message: self.message,
+            acl: &None,
+        })
+        .map_err(|why| why.0)
+    }
+}
+
+/// Registers the cancellation token of a batched command by the id of its invocation
+#[doc(hidden)]
+pub fn cancellation_token<R: Runtime>(
+    message: &InvokeMessage<R>,
+    args: &Value,
+    command: &'static str,
+    key: &'static str,
+) -> Result<CancellationToken, Value> {
+    let invocation_id =
+        FromBatchedCall::<u32>::resolve(&BatchArg::new(message, args, command, key, PhantomData))?;
+    let label = message.webview_ref().label().to_string();
+
+    Ok(CancellationToken::register(label, invocation_id))
+}
+
+/// Converts the returned `Result` of a batched command into its response
+#[doc(hidden)]
+pub struct ResultTag;
+
+impl ResultTag {
+    pub fn response<T: Serialize, E: Into<InvokeError>>(
+        self,
+        value: Result<T, E>,
+    ) -> Result<Value, Value> {
+        match value {
+            Ok(value) => ValueTag.response(value),
+            Err(why) => Err(why.into().0),
+        }
+    }
+}
+
+/// Converts the returned value of a batched command into its response
+#[doc(hidden)]
+pub struct ValueTag;
+
+impl ValueTag {
+    pub fn response<T: Serialize>(self, value: T) -> Result<Value, Value> {
+        serde_json::to_value(value).map_err(|why| Value::from(why.to_string()))
+    }
+}
+
+#[doc(hidden)]
+pub trait ResultKind {
+    fn batch_kind(&self) -> ResultTag {
+        ResultTag
+    }
+}
+
+impl<T, E> ResultKind for Result<T, E> {}
+
+#[doc(hidden)]
+pub trait ValueKind {
+    fn batch_kind(&self) -> ValueTag {
+        ValueTag
+    }
+}
+
+impl<T: Serialize> ValueKind for &T {}
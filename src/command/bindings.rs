@@ -1,3 +1,4 @@
+use futures::channel::oneshot;
 use js_sys::{JsString, RegExp};
 use serde::de::DeserializeOwned;
 use wasm_bindgen::prelude::*;
@@ -22,6 +23,37 @@ extern "C" {
         event: &str,
         closure: &Closure<dyn Fn(JsValue)>,
     ) -> Result<JsValue, JsValue>;
+
+    /// The binding for the frontend that listens to events with additional options
+    ///
+    /// Used to restrict the listener to events of a specific target.
+    #[cfg(feature = "event")]
+    #[doc(cfg(feature = "event"))]
+    #[allow(unused_doc_comments)]
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "event"], js_name = listen)]
+    pub async fn listen_with_options(
+        event: &str,
+        closure: &Closure<dyn Fn(JsValue)>,
+        options: JsValue,
+    ) -> Result<JsValue, JsValue>;
+
+    /// Binding for the window the frontend is running in
+    ///
+    /// [Window](https://v2.tauri.app/reference/javascript/api/namespacewindow/#getcurrentwindow)
+    #[cfg(feature = "event")]
+    #[doc(cfg(feature = "event"))]
+    #[allow(unused_doc_comments)]
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "window"], js_name = getCurrentWindow)]
+    pub fn get_current_window() -> JsValue;
+
+    /// The binding for the frontend that emits events to the host
+    ///
+    /// [Events](https://v2.tauri.app/develop/calling-rust/#event-system)
+    #[cfg(feature = "event")]
+    #[doc(cfg(feature = "event"))]
+    #[allow(unused_doc_comments)]
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "event"])]
+    pub async fn emit(event: &str, payload: JsValue) -> Result<JsValue, JsValue>;
 }
 
 enum InvokeResult {
@@ -30,9 +62,39 @@ enum InvokeResult {
     NotRegistered,
 }
 
+/// Invokes the command, or queues it into the [batch](crate::command::batch::batch)
+/// which is currently polled
+pub(crate) async fn send(command: String, args: JsValue) -> Result<JsValue, JsValue> {
+    match super::batch::enqueue(&command, args) {
+        Ok(receiver) => batched(receiver).await,
+        Err(args) => invoke(&command, args).await,
+    }
+}
+
+/// Awaits the response of an invocation, which was queued into a batch
+async fn batched(
+    receiver: oneshot::Receiver<Result<JsValue, JsValue>>,
+) -> Result<JsValue, JsValue> {
+    receiver
+        .await
+        .unwrap_or_else(|_| Err(JsValue::from_str("the batch was dropped")))
+}
+
 /// Wrapper for [invoke], to handle an unregistered function
+///
+/// The response is shared with identical invocations, when the command is cached.
 async fn wrapped_invoke(command: &str, args: JsValue) -> InvokeResult {
-    match invoke(command, args).await {
+    let response = match super::cache::current() {
+        Some((policy, key)) => super::cache::invoke(policy, key, command, args).await,
+        None => send(command.into(), args).await,
+    };
+
+    invoke_result(response)
+}
+
+/// Converts the response of [invoke], to handle an unregistered function
+fn invoke_result(response: Result<JsValue, JsValue>) -> InvokeResult {
+    match response {
         Ok(value) => InvokeResult::Ok(value),
         Err(value) => {
             if let Some(string) = value.dyn_ref::<JsString>() {
@@ -49,8 +111,19 @@ async fn wrapped_invoke(command: &str, args: JsValue) -> InvokeResult {
 }
 
 /// Wrapper for [wait_invoke], to send a command without waiting for it
+///
+/// The command is queued immediately into the [batch](crate::command::batch::batch)
+/// which is currently polled, as the spawned future isn't polled as part of the batch.
 pub fn fire_and_forget_invoke(command: &'static str, args: JsValue) {
-    wasm_bindgen_futures::spawn_local(wait_invoke(command, args))
+    let queued = super::batch::enqueue(command, args);
+    wasm_bindgen_futures::spawn_local(async move {
+        match queued {
+            Ok(receiver) => {
+                invoke_result(batched(receiver).await);
+            }
+            Err(args) => wait_invoke(command, args).await,
+        }
+    })
 }
 
 /// Wrapper for [invoke], to await a command execution without handling the returned values
@@ -89,3 +162,48 @@ where
         InvokeResult::NotRegistered => Ok(Default::default()),
     }
 }
+
+thread_local! {
+    static INVOCATION_ID: std::cell::Cell<u32> =
+        std::cell::Cell::new((js_sys::Math::random() * f64::from(u32::MAX)) as u32);
+}
+
+/// Returns a new id to identify an invocation of a command on the host
+///
+/// The ids start at a random offset, so that a reloaded frontend doesn't
+/// reuse the ids of invocations which might still be running on the host.
+pub fn next_invocation_id() -> u32 {
+    INVOCATION_ID.with(|id| {
+        let next = id.get().wrapping_add(1);
+        id.replace(next)
+    })
+}
+
+/// Guard that notifies the host when a command invocation is dropped before completion
+///
+/// Used by the generated bindings of commands that take a cancellation token.
+pub struct CancelGuard {
+    cancel: Option<Box<dyn FnOnce()>>,
+}
+
+impl CancelGuard {
+    /// Creates a guard which calls `cancel` when it is dropped
+    pub fn new(cancel: impl FnOnce() + 'static) -> Self {
+        Self {
+            cancel: Some(Box::new(cancel)),
+        }
+    }
+
+    /// Marks the invocation as completed, so that the host isn't notified anymore
+    pub fn disarm(mut self) {
+        self.cancel.take();
+    }
+}
+
+impl Drop for CancelGuard {
+    fn drop(&mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            cancel()
+        }
+    }
+}
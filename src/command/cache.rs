@@ -0,0 +1,321 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+#[cfg(feature = "event")]
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+use futures::future::{LocalBoxFuture, Shared};
+use futures::FutureExt;
+use js_sys::{Date, JSON};
+#[cfg(feature = "event")]
+use serde::de::IgnoredAny;
+use serde::Serialize;
+use serde_wasm_bindgen::Serializer;
+use wasm_bindgen::JsValue;
+
+use crate::command::bindings::send;
+#[cfg(all(doc, feature = "event"))]
+use crate::event::Field;
+#[cfg(feature = "event")]
+use crate::event::ListenHandle;
+
+type Response = Result<JsValue, JsValue>;
+
+/// How the results of a command are shared between its invocations
+///
+/// Created by the generated bindings of commands with `idempotent` or `cache` attribute.
+#[derive(Debug, Clone, Copy)]
+pub struct CachePolicy {
+    store: bool,
+    ttl: Option<f64>,
+}
+
+impl CachePolicy {
+    /// Identical invocations share the pending request, but the result isn't kept
+    pub const fn deduplicate() -> Self {
+        Self {
+            store: false,
+            ttl: None,
+        }
+    }
+
+    /// Identical invocations share the pending request, and a successful result is kept
+    /// for `ttl` seconds or until it is invalidated
+    pub const fn cache(ttl: Option<f64>) -> Self {
+        Self { store: true, ttl }
+    }
+}
+
+enum Entry {
+    Pending {
+        id: u64,
+        response: Shared<LocalBoxFuture<'static, Response>>,
+    },
+    Cached {
+        response: JsValue,
+        expires_at: Option<f64>,
+    },
+}
+
+thread_local! {
+    static CURRENT_POLICY: RefCell<Option<(CachePolicy, String)>> = const { RefCell::new(None) };
+    static NEXT_ID: Cell<u64> = const { Cell::new(0) };
+    static ENTRIES: RefCell<HashMap<String, HashMap<String, Entry>>> = RefCell::default();
+    static CALLBACKS: RefCell<HashMap<String, HashMap<u64, Rc<dyn Fn()>>>> = RefCell::default();
+}
+
+#[cfg(feature = "event")]
+thread_local! {
+    static COMMANDS: RefCell<HashSet<&'static str>> = RefCell::default();
+    static DEPENDENTS: RefCell<HashMap<&'static str, Vec<&'static str>>> = RefCell::default();
+    static REGISTRATIONS: RefCell<HashSet<&'static str>> = RefCell::default();
+    static LISTENERS: RefCell<Vec<ListenHandle>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Builds the key, which identifies identical invocations, from the arguments of a command
+///
+/// The arguments are serialized as plain JSON, so that maps are part of the key. Returns
+/// `None`, when the arguments can't be represented as JSON.
+///
+/// Used by the generated bindings of commands with `idempotent` or `cache` attribute.
+pub fn key<A: Serialize>(args: &A) -> Option<String> {
+    let value = args.serialize(&Serializer::json_compatible()).ok()?;
+    JSON::stringify(&value).ok().map(String::from)
+}
+
+/// Applies the policy to the invocation of the given binding future
+///
+/// Without a `key` the invocation isn't shared or cached.
+///
+/// Used by the generated bindings of commands with `idempotent` or `cache` attribute.
+pub fn cached<F: Future>(policy: CachePolicy, key: Option<String>, future: F) -> Cached<F> {
+    if key.is_none() {
+        log::warn!("No cache key could be built from the arguments, the invocation isn't cached");
+    }
+
+    Cached {
+        invocation: key.map(|key| (policy, key)),
+        future: Box::pin(future),
+    }
+}
+
+/// Future of a binding, which invocation is shared or cached
+pub struct Cached<F: Future> {
+    invocation: Option<(CachePolicy, String)>,
+    future: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for Cached<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let previous = CURRENT_POLICY.replace(self.invocation.clone());
+        let poll = self.future.as_mut().poll(cx);
+        CURRENT_POLICY.set(previous);
+
+        poll
+    }
+}
+
+/// Returns the policy and key of the invocation, which is currently polled
+pub(crate) fn current() -> Option<(CachePolicy, String)> {
+    CURRENT_POLICY.take()
+}
+
+/// Invokes the command, or shares a pending or cached response of an identical invocation
+pub(crate) async fn invoke(
+    policy: CachePolicy,
+    key: String,
+    command: &str,
+    args: JsValue,
+) -> Response {
+    let pending = ENTRIES.with_borrow_mut(|entries| {
+        let entries = entries.entry(command.into()).or_default();
+
+        match entries.get(&key) {
+            Some(Entry::Pending { id, response }) => return Ok((*id, response.clone())),
+            Some(Entry::Cached {
+                response,
+                expires_at,
+            }) if expires_at.is_none_or(|expires_at| Date::now() < expires_at) => {
+                log::trace!("Using cached response of {command}");
+                return Err(response.clone());
+            }
+            _ => {}
+        }
+
+        let id = NEXT_ID.replace(NEXT_ID.get() + 1);
+        let response = send(command.into(), args).boxed_local().shared();
+        entries.insert(
+            key.clone(),
+            Entry::Pending {
+                id,
+                response: response.clone(),
+            },
+        );
+
+        Ok((id, response))
+    });
+
+    let (id, response) = match pending {
+        Ok(pending) => pending,
+        Err(cached) => return Ok(cached),
+    };
+
+    let response = response.await;
+    settle(policy, command, &key, id, &response);
+
+    response
+}
+
+/// Replaces the pending entry with the response, when it wasn't invalidated in the meantime
+fn settle(policy: CachePolicy, command: &str, key: &str, id: u64, response: &Response) {
+    ENTRIES.with_borrow_mut(|entries| {
+        let Some(entries) = entries.get_mut(command) else {
+            return;
+        };
+        if !matches!(entries.get(key), Some(Entry::Pending { id: pending, .. }) if *pending == id) {
+            return;
+        }
+
+        match response {
+            Ok(response) if policy.store => {
+                let expires_at = policy.ttl.map(|ttl| Date::now() + ttl * 1000.0);
+                let response = response.clone();
+                entries.insert(
+                    key.into(),
+                    Entry::Cached {
+                        response,
+                        expires_at,
+                    },
+                );
+            }
+            _ => {
+                entries.remove(key);
+            }
+        }
+    })
+}
+
+/// Discards the cached results of a command and notifies its [InvalidateHandle]s
+///
+/// Pending invocations aren't affected, but their results won't be cached.
+pub fn invalidate(command: &str) {
+    log::trace!("Invalidating cached responses of {command}");
+    ENTRIES.with_borrow_mut(|entries| entries.remove(command));
+
+    let callbacks = CALLBACKS.with_borrow(|callbacks| {
+        callbacks
+            .get(command)
+            .map(|callbacks| callbacks.values().cloned().collect::<Vec<_>>())
+            .unwrap_or_default()
+    });
+    callbacks.iter().for_each(|callback| callback());
+}
+
+/// Discards the cached results of all commands
+pub fn invalidate_all() {
+    ENTRIES.with_borrow_mut(HashMap::clear);
+
+    let commands = CALLBACKS.with_borrow(|callbacks| callbacks.keys().cloned().collect::<Vec<_>>());
+    commands.iter().for_each(|command| invalidate(command));
+}
+
+/// Registers a callback, which is called every time the results of a command are invalidated
+///
+/// Intended to refetch results that are bound to the ui. The callback is removed, when the
+/// returned handle is dropped.
+pub fn on_invalidate(command: &str, callback: impl Fn() + 'static) -> InvalidateHandle {
+    let id = NEXT_ID.replace(NEXT_ID.get() + 1);
+    CALLBACKS.with_borrow_mut(|callbacks| {
+        callbacks
+            .entry(command.into())
+            .or_default()
+            .insert(id, Rc::new(callback))
+    });
+
+    InvalidateHandle {
+        command: command.into(),
+        id,
+    }
+}
+
+/// Handle of a callback registered with [on_invalidate]
+pub struct InvalidateHandle {
+    command: String,
+    id: u64,
+}
+
+impl Drop for InvalidateHandle {
+    fn drop(&mut self) {
+        CALLBACKS.with_borrow_mut(|callbacks| {
+            if let Some(command) = callbacks.get_mut(&self.command) {
+                command.remove(&self.id);
+            }
+        })
+    }
+}
+
+/// Invalidates the results of a command, every time one of the given events is emitted
+///
+/// Used by the generated bindings of commands with the `invalidate_on` attribute, before the
+/// command is invoked. The events are the [Field::CHANGE_EVENTS] of the listed fields, the
+/// dependencies of a command are registered with its first invocation and a listener for
+/// each event is kept for the lifetime of the frontend. The listeners are registered in the
+/// background, once registered the results of the dependent commands are invalidated, as an
+/// event could have been missed in the meantime.
+#[cfg(feature = "event")]
+#[doc(cfg(feature = "event"))]
+pub fn invalidate_on(command: &'static str, events: &[&[&'static str]]) {
+    if !COMMANDS.with_borrow_mut(|commands| commands.insert(command)) {
+        return;
+    }
+
+    for &event in events.iter().flat_map(|events| events.iter()) {
+        DEPENDENTS.with_borrow_mut(|dependents| {
+            let commands = dependents.entry(event).or_default();
+            if !commands.contains(&command) {
+                commands.push(command)
+            }
+        });
+
+        if REGISTRATIONS.with_borrow_mut(|registrations| registrations.insert(event)) {
+            wasm_bindgen_futures::spawn_local(listen_for_invalidation(event));
+        }
+    }
+}
+
+#[cfg(feature = "event")]
+fn dependents(event: &str) -> Vec<&'static str> {
+    DEPENDENTS
+        .with_borrow(|dependents| dependents.get(event).cloned())
+        .unwrap_or_default()
+}
+
+#[cfg(feature = "event")]
+async fn listen_for_invalidation(event: &'static str) {
+    let result = ListenHandle::register(event, move |_: IgnoredAny| {
+        dependents(event).into_iter().for_each(invalidate)
+    })
+    .await;
+
+    match result {
+        Ok(handle) => {
+            LISTENERS.with_borrow_mut(|listeners| listeners.push(handle));
+            dependents(event).into_iter().for_each(invalidate)
+        }
+        Err(why) => {
+            log::error!("Listening to {event} for invalidations failed: {why}");
+            // allows another attempt with the next invocation of the dependent commands
+            REGISTRATIONS.with_borrow_mut(|registrations| registrations.remove(event));
+            COMMANDS.with_borrow_mut(|commands| {
+                for command in dependents(event) {
+                    commands.remove(command);
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, Weak};
+use std::task::{Context, Poll, Waker};
+
+use serde::Deserialize;
+use tauri::ipc::{CommandArg, CommandItem, InvokeError};
+use tauri::Runtime;
+
+#[cfg(doc)]
+use tauri_interop_macro::command;
+
+/// Key of a running invocation, consisting of the webview label and the invocation id
+type InvocationKey = (String, u32);
+
+static INVOCATIONS: LazyLock<Mutex<HashMap<InvocationKey, Weak<Inner>>>> =
+    LazyLock::new(Default::default);
+
+#[derive(Default)]
+struct Inner {
+    key: Option<InvocationKey>,
+    cancelled: AtomicBool,
+    /// The waker of each pending [Cancelled], keyed by its slot
+    wakers: Mutex<HashMap<u64, Waker>>,
+    next_slot: AtomicU64,
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        let Some(key) = self.key.take() else { return };
+        let mut invocations = INVOCATIONS.lock().unwrap();
+        if invocations
+            .get(&key)
+            .is_some_and(|inner| inner.strong_count() == 0)
+        {
+            invocations.remove(&key);
+        }
+    }
+}
+
+/// Token that signals that the frontend isn't interested in the result of a command anymore
+///
+/// When used as argument of a [command], the argument is removed from the generated
+/// wasm binding. Instead, the binding sends an invocation id and notifies the host via
+/// the generated `cancel_{command}` command when its future is dropped before completion.
+///
+/// The cancellation is cooperative, the command has to check [CancellationToken::is_cancelled]
+/// or await [CancellationToken::cancelled] to stop its work.
+///
+/// ### Example
+///
+/// ```
+/// use tauri_interop::command::TauriCancellationToken;
+///
+/// #[tauri_interop::command]
+/// async fn long_export(rows: u32, token: TauriCancellationToken) -> Result<u32, String> {
+///     for row in 0..rows {
+///         if token.is_cancelled() {
+///             return Err(format!("cancelled after {row} rows"));
+///         }
+///     }
+///
+///     Ok(rows)
+/// }
+///
+/// fn main() {}
+/// ```
+#[derive(Clone, Default)]
+pub struct CancellationToken(Arc<Inner>);
+
+impl CancellationToken {
+    /// Creates a token that isn't bound to any invocation
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn register(label: String, invocation_id: u32) -> Self {
+        let key = (label, invocation_id);
+        let inner = Arc::new(Inner {
+            key: Some(key.clone()),
+            cancelled: AtomicBool::new(false),
+            wakers: Mutex::default(),
+            next_slot: AtomicU64::new(0),
+        });
+
+        INVOCATIONS
+            .lock()
+            .unwrap()
+            .insert(key, Arc::downgrade(&inner));
+
+        Self(inner)
+    }
+
+    /// Returns `true` when the token was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Cancels the token and wakes all tasks waiting on [CancellationToken::cancelled]
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::Release);
+        self.0
+            .wakers
+            .lock()
+            .unwrap()
+            .drain()
+            .for_each(|(_, waker)| waker.wake());
+    }
+
+    /// Returns a future that resolves as soon as the token is cancelled
+    pub fn cancelled(&self) -> Cancelled<'_> {
+        Cancelled {
+            token: self,
+            slot: None,
+        }
+    }
+}
+
+/// Future returned by [CancellationToken::cancelled]
+pub struct Cancelled<'t> {
+    token: &'t CancellationToken,
+    /// The slot of the registered waker, which is replaced on every poll
+    slot: Option<u64>,
+}
+
+impl Future for Cancelled<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if this.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+
+        let inner = &this.token.0;
+        let slot = *this
+            .slot
+            .get_or_insert_with(|| inner.next_slot.fetch_add(1, Ordering::Relaxed));
+        let mut wakers = inner.wakers.lock().unwrap();
+        match wakers.get_mut(&slot) {
+            Some(waker) if waker.will_wake(cx.waker()) => {}
+            Some(waker) => waker.clone_from(cx.waker()),
+            None => {
+                wakers.insert(slot, cx.waker().clone());
+            }
+        }
+        drop(wakers);
+
+        // the token could have been cancelled while registering the waker
+        if this.token.is_cancelled() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for Cancelled<'_> {
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot {
+            self.token.0.wakers.lock().unwrap().remove(&slot);
+        }
+    }
+}
+
+impl<'de, R: Runtime> CommandArg<'de, R> for CancellationToken {
+    fn from_command(command: CommandItem<'de, R>) -> Result<Self, InvokeError> {
+        let (name, key) = (command.name, command.key);
+        let label = command.message.webview_ref().label().to_string();
+        let invocation_id =
+            u32::deserialize(command).map_err(|why| tauri::Error::InvalidArgs(name, key, why))?;
+
+        Ok(Self::register(label, invocation_id))
+    }
+}
+
+/// Cancels the running invocation with the given id, that was invoked by the webview with `label`
+///
+/// Used by the generated `cancel_{command}` commands. Does nothing if the invocation
+/// already completed.
+pub fn cancel_invocation(label: &str, invocation_id: u32) {
+    let inner = INVOCATIONS
+        .lock()
+        .unwrap()
+        .remove(&(label.to_string(), invocation_id))
+        .and_then(|inner| inner.upgrade());
+
+    match inner {
+        Some(inner) => {
+            log::trace!("Cancelling invocation {invocation_id} of {label}");
+            CancellationToken(inner).cancel()
+        }
+        None => log::trace!("Invocation {invocation_id} of {label} already completed"),
+    }
+}
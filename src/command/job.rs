@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(any(target_family = "wasm", doc))]
+#[doc(cfg(target_family = "wasm"))]
+pub use handle::*;
+#[cfg(not(target_family = "wasm"))]
+#[doc(cfg(not(target_family = "wasm")))]
+pub use registry::*;
+#[cfg(doc)]
+use tauri_interop_macro::command;
+
+/// wasm handle to observe and control a running job
+#[cfg(any(target_family = "wasm", doc))]
+#[doc(cfg(target_family = "wasm"))]
+mod handle;
+
+/// spawning and tracking of jobs on the host
+#[cfg(not(target_family = "wasm"))]
+#[doc(cfg(not(target_family = "wasm")))]
+mod registry;
+
+/// Identifier of a job, returned when a job [command] is invoked
+pub type JobId = u32;
+
+/// The state of a job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum JobStatus<P> {
+    /// The job is still running, with the last reported progress if there is any
+    Running(Option<P>),
+    /// The job completed and the result can be retrieved
+    Completed,
+    /// The job was cancelled before it completed
+    Cancelled,
+    /// The job panicked or its result couldn't be serialized
+    Failed(String),
+}
+
+impl<P> Default for JobStatus<P> {
+    fn default() -> Self {
+        Self::Running(None)
+    }
+}
+
+/// Errors that can occur while interacting with a job
+#[derive(Debug, Clone, Serialize, Deserialize, thiserror::Error)]
+pub enum JobError {
+    /// The job doesn't exist or its result was already retrieved
+    #[error("job {0} doesn't exist")]
+    NotFound(JobId),
+    /// The job was cancelled before it completed
+    #[error("job was cancelled")]
+    Cancelled,
+    /// The job panicked or its result couldn't be serialized
+    #[error("job failed: {0}")]
+    Failed(String),
+    /// Invoking one of the job commands failed
+    #[error("invoking the job command failed: {0}")]
+    Invoke(String),
+}
+
+/// Returns the name of the event, which is used to emit the progress of a job
+pub fn progress_event(command: &str, id: JobId) -> String {
+    format!("{command}::job::{id}")
+}
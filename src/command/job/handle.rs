@@ -0,0 +1,196 @@
+use std::future::{Future, IntoFuture};
+use std::marker::PhantomData;
+use std::pin::Pin;
+#[cfg(feature = "event")]
+use std::task::{Context, Poll};
+#[cfg(feature = "event")]
+use std::{cell::Cell, rc::Rc};
+
+#[cfg(feature = "event")]
+use futures::{channel::mpsc, Stream, StreamExt};
+use serde::{de::DeserializeOwned, Serialize};
+use wasm_bindgen::JsValue;
+
+use super::{JobError, JobId, JobStatus};
+use crate::command::bindings::invoke;
+#[cfg(feature = "event")]
+use crate::event::{ListenError, ListenHandle};
+
+#[derive(Serialize)]
+struct JobArgs {
+    job_id: JobId,
+}
+
+async fn invoke_job<T: DeserializeOwned>(command: &str, args: JsValue) -> Result<T, JobError> {
+    match invoke(command, args).await {
+        Ok(value) => serde_wasm_bindgen::from_value(value)
+            .map_err(|why| JobError::Invoke(format!("conversion failed: {why}"))),
+        Err(value) => Err(serde_wasm_bindgen::from_value(value.clone())
+            .unwrap_or_else(|_| JobError::Invoke(format!("{value:?}")))),
+    }
+}
+
+/// Handle to a job, which was started by invoking a job command
+///
+/// The handle can be awaited to retrieve the result of the job. The result can
+/// only be retrieved once, afterward the job doesn't exist on the host anymore.
+/// Dropping the handle without retrieving the result discards the job on the host,
+/// the job itself keeps running until it completes or is cancelled.
+///
+/// ### Example
+///
+/// ```ignore
+/// async fn import(files: Vec<String>) {
+///     let job = api::cmd::import(files).await.expect("the job was started");
+///
+///     let mut progress = job.progress().await.expect("listening to the progress");
+///     wasm_bindgen_futures::spawn_local(async move {
+///         while let Some(imported) = progress.next().await {
+///             log::info!("imported {imported} files");
+///         }
+///     });
+///
+///     let imported = job.await.expect("the job completed");
+/// }
+/// ```
+pub struct JobHandle<P, T> {
+    command: &'static str,
+    id: JobId,
+    retrieved: bool,
+    _marker: PhantomData<fn() -> (P, T)>,
+}
+
+impl<P, T> JobHandle<P, T>
+where
+    P: DeserializeOwned + 'static,
+    T: DeserializeOwned + 'static,
+{
+    /// Invokes the given job command and returns the handle to the started job
+    ///
+    /// Used by the generated bindings of job commands.
+    pub async fn start(command: &'static str, args: JsValue) -> Result<Self, JobError> {
+        let id = invoke_job(command, args).await?;
+        log::trace!("Started job {id} of {command}");
+
+        Ok(Self {
+            command,
+            id,
+            retrieved: false,
+            _marker: PhantomData,
+        })
+    }
+
+    /// The id of the job
+    pub fn id(&self) -> JobId {
+        self.id
+    }
+
+    fn args(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&JobArgs { job_id: self.id }).expect("serialized arguments")
+    }
+
+    /// Requests the current status of the job
+    pub async fn status(&self) -> Result<JobStatus<P>, JobError> {
+        invoke_job(&format!("{}_status", self.command), self.args()).await
+    }
+
+    /// Requests the cancellation of the job, without waiting for it
+    pub fn cancel(&self) {
+        let command = format!("{}_cancel", self.command);
+        let args = self.args();
+
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(why) = invoke_job::<()>(&command, args).await {
+                log::error!("{why}")
+            }
+        })
+    }
+
+    /// Registers a listener for the progress of the job, and returns it as [Stream]
+    ///
+    /// The last progress, which was reported before the listener was registered, is the
+    /// first item of the stream. The listener is detached when the stream is dropped.
+    #[cfg(feature = "event")]
+    #[doc(cfg(feature = "event"))]
+    pub async fn progress(&self) -> Result<JobProgress<P>, ListenError> {
+        let (sender, receiver) = mpsc::unbounded();
+        let received = Rc::new(Cell::new(false));
+        let listen_handle = ListenHandle::register(super::progress_event(self.command, self.id), {
+            let sender = sender.clone();
+            let received = received.clone();
+            move |progress| {
+                received.set(true);
+                let _ = sender.unbounded_send(progress);
+            }
+        })
+        .await?;
+
+        // a received progress is at least as recent as the one of the status
+        if let Ok(JobStatus::Running(Some(progress))) = self.status().await {
+            if !received.get() {
+                let _ = sender.unbounded_send(progress);
+            }
+        }
+
+        Ok(JobProgress {
+            receiver,
+            _listen_handle: listen_handle,
+        })
+    }
+
+    /// Waits for the job to complete and returns its result
+    pub async fn result(mut self) -> Result<T, JobError> {
+        let result = invoke_job(&format!("{}_result", self.command), self.args()).await;
+        // the job was removed by retrieving its result
+        self.retrieved = true;
+        result
+    }
+}
+
+impl<P, T> Drop for JobHandle<P, T> {
+    fn drop(&mut self) {
+        if self.retrieved {
+            return;
+        }
+
+        let command = format!("{}_discard", self.command);
+        let args = serde_wasm_bindgen::to_value(&JobArgs { job_id: self.id })
+            .expect("serialized arguments");
+
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(why) = invoke_job::<()>(&command, args).await {
+                log::error!("{why}")
+            }
+        })
+    }
+}
+
+impl<P, T> IntoFuture for JobHandle<P, T>
+where
+    P: DeserializeOwned + 'static,
+    T: DeserializeOwned + 'static,
+{
+    type Output = Result<T, JobError>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Self::Output>>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(self.result())
+    }
+}
+
+/// [Stream] of the progress updates of a job, returned by [JobHandle::progress]
+#[cfg(feature = "event")]
+#[doc(cfg(feature = "event"))]
+pub struct JobProgress<P> {
+    receiver: mpsc::UnboundedReceiver<P>,
+    _listen_handle: ListenHandle,
+}
+
+#[cfg(feature = "event")]
+impl<P> Stream for JobProgress<P> {
+    type Item = P;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use futures::future::{self, Either};
+use futures::FutureExt;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+
+use super::{progress_event, JobError, JobId, JobStatus};
+use crate::command::{CancellationToken, Cancelled};
+
+static NEXT_JOB_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Duration after which a finished job, whose result wasn't retrieved, is evicted
+pub const FINISHED_JOB_TTL: Duration = Duration::from_secs(5 * 60);
+
+static JOBS: LazyLock<Mutex<HashMap<JobId, Job>>> = LazyLock::new(Default::default);
+
+struct Job {
+    token: CancellationToken,
+    progress: Option<Value>,
+    outcome: Option<Result<Value, JobError>>,
+    finished: Option<Instant>,
+    discarded: bool,
+    /// The waker of each pending [result] request, keyed by its slot
+    wakers: HashMap<u64, Waker>,
+    next_slot: u64,
+}
+
+impl Job {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.finished
+            .is_some_and(|finished| now.duration_since(finished) >= FINISHED_JOB_TTL)
+    }
+}
+
+/// Context of a running job, used to report the progress and to check for a cancellation
+///
+/// When used as argument of a job command, the context is provided by the generated host
+/// command and removed from the generated wasm binding.
+///
+/// ### Example
+///
+/// ```
+/// use tauri_interop::command::TauriJob;
+///
+/// #[tauri_interop::command(job)]
+/// async fn import(files: Vec<String>, job: TauriJob<usize>) -> usize {
+///     for (imported, _file) in files.iter().enumerate() {
+///         if job.is_cancelled() {
+///             return imported;
+///         }
+///         job.progress(imported);
+///     }
+///
+///     files.len()
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct JobContext<P> {
+    id: JobId,
+    event: String,
+    handle: AppHandle,
+    token: CancellationToken,
+    _progress: PhantomData<fn(P)>,
+}
+
+impl<P: Serialize> JobContext<P> {
+    /// The id of the running job
+    pub fn id(&self) -> JobId {
+        self.id
+    }
+
+    /// Reports the current progress of the job
+    ///
+    /// The progress is stored for [status] requests and emitted to the frontend.
+    pub fn progress(&self, progress: P) {
+        let progress = match serde_json::to_value(progress) {
+            Ok(progress) => progress,
+            Err(why) => {
+                log::error!("Serializing progress of job {} failed: {why}", self.id);
+                return;
+            }
+        };
+
+        if let Some(job) = JOBS.lock().unwrap().get_mut(&self.id) {
+            job.progress = Some(progress.clone());
+        }
+
+        if let Err(why) = self.handle.emit(&self.event, progress) {
+            log::error!("Emitting progress of job {} failed: {why}", self.id)
+        }
+    }
+
+    /// Returns `true` when the job was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Returns a future that resolves as soon as the job is cancelled
+    pub fn cancelled(&self) -> Cancelled<'_> {
+        self.token.cancelled()
+    }
+
+    /// The token that is cancelled when the job is cancelled
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+/// Spawns the future returned by `job` and tracks it until its result is retrieved
+///
+/// Used by the generated host command of a job command. A cancellation of the job
+/// cancels its token and drops the future at the next await point. Jobs, which finished
+/// more than [FINISHED_JOB_TTL] ago without their result being retrieved, are evicted.
+pub fn spawn<P, F, Fut>(handle: AppHandle, command: &str, job: F) -> JobId
+where
+    F: FnOnce(JobContext<P>) -> Fut,
+    Fut: Future + Send + 'static,
+    Fut::Output: Serialize,
+{
+    let id = NEXT_JOB_ID.fetch_add(1, Ordering::Relaxed);
+    let token = CancellationToken::new();
+
+    let mut jobs = JOBS.lock().unwrap();
+    let now = Instant::now();
+    jobs.retain(|_, job| !job.is_expired(now));
+    jobs.insert(
+        id,
+        Job {
+            token: token.clone(),
+            progress: None,
+            outcome: None,
+            finished: None,
+            discarded: false,
+            wakers: HashMap::new(),
+            next_slot: 0,
+        },
+    );
+    drop(jobs);
+
+    let context = JobContext {
+        id,
+        event: progress_event(command, id),
+        handle,
+        token: token.clone(),
+        _progress: PhantomData,
+    };
+    let job = AssertUnwindSafe(job(context)).catch_unwind();
+
+    log::trace!("Spawning job {id} of {command}");
+    tauri::async_runtime::spawn(async move {
+        let outcome = match future::select(Box::pin(job), Box::pin(token.cancelled())).await {
+            Either::Left((Ok(output), _)) => {
+                serde_json::to_value(output).map_err(|why| JobError::Failed(why.to_string()))
+            }
+            Either::Left((Err(_), _)) => Err(JobError::Failed("the job panicked".into())),
+            Either::Right(_) => Err(JobError::Cancelled),
+        };
+
+        complete(id, outcome)
+    });
+
+    id
+}
+
+fn complete(id: JobId, outcome: Result<Value, JobError>) {
+    let mut jobs = JOBS.lock().unwrap();
+    let Some(job) = jobs.get_mut(&id) else { return };
+
+    log::trace!("Job {id} completed");
+    if job.discarded {
+        jobs.remove(&id);
+        return;
+    }
+
+    job.outcome.get_or_insert(outcome);
+    job.finished = Some(Instant::now());
+    job.wakers.drain().for_each(|(_, waker)| waker.wake());
+}
+
+/// Returns the status of a job with the last reported progress
+pub fn status(id: JobId) -> Result<JobStatus<Value>, JobError> {
+    let jobs = JOBS.lock().unwrap();
+    let job = jobs.get(&id).ok_or(JobError::NotFound(id))?;
+
+    Ok(match &job.outcome {
+        None => JobStatus::Running(job.progress.clone()),
+        Some(Ok(_)) => JobStatus::Completed,
+        Some(Err(JobError::Cancelled)) => JobStatus::Cancelled,
+        Some(Err(why)) => JobStatus::Failed(why.to_string()),
+    })
+}
+
+/// Cancels a running job
+pub fn cancel(id: JobId) {
+    match JOBS.lock().unwrap().get(&id) {
+        Some(job) => job.token.cancel(),
+        None => log::trace!("Job {id} doesn't exist anymore"),
+    }
+}
+
+/// Discards a job, whose result isn't needed anymore
+///
+/// A finished job is removed immediately, a running job as soon as it completes. The job
+/// isn't cancelled, pending [result] requests resolve with [JobError::NotFound].
+pub fn discard(id: JobId) {
+    let mut jobs = JOBS.lock().unwrap();
+    let Some(job) = jobs.get_mut(&id) else {
+        log::trace!("Job {id} doesn't exist anymore");
+        return;
+    };
+
+    log::trace!("Discarding job {id}");
+    job.wakers.drain().for_each(|(_, waker)| waker.wake());
+    if job.outcome.is_some() {
+        jobs.remove(&id);
+    } else {
+        job.discarded = true;
+    }
+}
+
+/// Waits for a job to complete and returns its result
+///
+/// The job is removed afterward, so that the result can be only retrieved once.
+pub async fn result(id: JobId) -> Result<Value, JobError> {
+    JobResult { id, slot: None }.await
+}
+
+/// Future returned by [result]
+struct JobResult {
+    id: JobId,
+    /// The slot of the registered waker, which is replaced on every poll
+    slot: Option<u64>,
+}
+
+impl Future for JobResult {
+    type Output = Result<Value, JobError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut jobs = JOBS.lock().unwrap();
+        let Some(job) = jobs.get_mut(&this.id).filter(|job| !job.discarded) else {
+            return Poll::Ready(Err(JobError::NotFound(this.id)));
+        };
+
+        if let Some(outcome) = job.outcome.take() {
+            jobs.remove(&this.id);
+            return Poll::Ready(outcome);
+        }
+
+        let slot = *this.slot.get_or_insert_with(|| {
+            job.next_slot += 1;
+            job.next_slot
+        });
+        match job.wakers.get_mut(&slot) {
+            Some(waker) if waker.will_wake(cx.waker()) => {}
+            Some(waker) => waker.clone_from(cx.waker()),
+            None => {
+                job.wakers.insert(slot, cx.waker().clone());
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for JobResult {
+    fn drop(&mut self) {
+        let Some(slot) = self.slot else { return };
+        if let Some(job) = JOBS.lock().unwrap().get_mut(&self.id) {
+            job.wakers.remove(&slot);
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use tauri::{AppHandle, State, Window};
+use tauri::{AppHandle, State, Webview, Window};
+
+use super::{job::JobContext, CancellationToken};
 
 #[cfg(doc)]
 use tauri_interop_macro::command;
@@ -11,3 +13,12 @@ pub type TauriWindow = Window;
 
 /// Type alias to easier identify [AppHandle] via [command] macro
 pub type TauriAppHandle = AppHandle;
+
+/// Type alias to easier identify [Webview] via [command] macro
+pub type TauriWebview = Webview;
+
+/// Type alias to easier identify [CancellationToken] via [command] macro
+pub type TauriCancellationToken = CancellationToken;
+
+/// Type alias to easier identify [JobContext] via [command] macro
+pub type TauriJob<P = ()> = JobContext<P>;
@@ -0,0 +1,206 @@
+use std::cell::RefCell;
+use std::future::Future;
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+
+use crate::command::cache::on_invalidate;
+
+#[cfg(feature = "event")]
+use crate::event::{
+    Field, FieldState, ListenHandle, ListenTarget, Parent, RevisionTracker, Revisioned,
+};
+
+/// Registers a listener to a [Field] and binds the returned signal to its changes
+///
+/// Providing [None] will unwrap into the default value. When feature `initial_value`
+/// is enabled [None] will try to get the value from tauri.
+///
+/// The listener is detached, when the component is dropped.
+///
+/// ### Example
+///
+/// ```ignore
+/// #[component]
+/// fn Foo() -> Element {
+///     let foo = tauri_interop::dioxus::use_field::<TestState, test_mod::FFoo>(None);
+///
+///     rsx! { h1 { "{foo}" } }
+/// }
+/// ```
+#[cfg(feature = "event")]
+#[doc(cfg(feature = "event"))]
+pub fn use_field<P, F>(initial_value: Option<F::Type>) -> ReadOnlySignal<F::Type>
+where
+    P: Parent + 'static,
+    F: Field<P> + 'static,
+    F::Type: Default,
+{
+    #[cfg(feature = "initial_value")]
+    let acquire_initial_value = initial_value.is_none();
+    let mut value = use_signal(move || initial_value.unwrap_or_default());
+
+    // holds the handle until the component is dropped
+    let handle = use_hook(|| Rc::new(RefCell::new(None)));
+    let mounted = use_hook(|| Rc::new(RefCell::new(true)));
+
+    use_hook({
+        let handle = handle.clone();
+        let mounted = mounted.clone();
+        move || {
+            wasm_bindgen_futures::spawn_local(async move {
+                let revision = RevisionTracker::default();
+
+                // the listener is registered first, so that no update is missed while acquiring the value
+                let listen_handle = F::subscribe(ListenTarget::Any, {
+                    let revision = revision.clone();
+                    move |update: Revisioned<F::Type>| {
+                        log::trace!("update for {}", F::EVENT_NAME);
+                        if revision.accept(update.revision) {
+                            let mut value = value;
+                            value.set(update.value)
+                        }
+                    }
+                })
+                .await;
+
+                match listen_handle {
+                    Ok(listen_handle) if *mounted.borrow() => {
+                        handle.replace(Some(listen_handle));
+                    }
+                    // the component is dropped already, in which case the handle is dropped
+                    Ok(_) => return,
+                    Err(why) => log::error!("{why}"),
+                }
+
+                #[cfg(feature = "initial_value")]
+                if acquire_initial_value {
+                    match F::get_revisioned_value().await {
+                        // an emitted value could be newer than the requested one
+                        Ok(initial) if *mounted.borrow() && revision.accept(initial.revision) => {
+                            value.set(initial.value)
+                        }
+                        Ok(_) => {}
+                        Err(why) => log::error!("{why}"),
+                    }
+                }
+            })
+        }
+    });
+
+    use_drop(move || {
+        mounted.replace(false);
+        handle.take();
+    });
+
+    value.into()
+}
+
+/// Registers a listener to a [Field] and binds the returned signal to its [FieldState]
+///
+/// See [ListenHandle::subscribe_state] for the transitions of the state. The listener is
+/// detached, when the component is dropped.
+///
+/// ### Example
+///
+/// ```ignore
+/// #[component]
+/// fn Foo() -> Element {
+///     let foo = tauri_interop::dioxus::use_field_state::<TestState, test_mod::FFoo>();
+///
+///     match &*foo.read() {
+///         FieldState::Ready(foo) => rsx! { h1 { "{foo}" } },
+///         _ => rsx! { h1 { "loading..." } },
+///     }
+/// }
+/// ```
+#[cfg(feature = "event")]
+#[doc(cfg(feature = "event"))]
+pub fn use_field_state<P, F>() -> ReadOnlySignal<FieldState<F::Type>>
+where
+    P: Parent + 'static,
+    F: Field<P> + 'static,
+{
+    let state = use_signal(|| FieldState::Loading);
+
+    // holds the handle until the component is dropped
+    let handle = use_hook(|| Rc::new(RefCell::new(None)));
+    let mounted = use_hook(|| Rc::new(RefCell::new(true)));
+
+    use_hook({
+        let handle = handle.clone();
+        let mounted = mounted.clone();
+        move || {
+            wasm_bindgen_futures::spawn_local(async move {
+                let listen_handle = ListenHandle::subscribe_state::<P, F>({
+                    let mounted = mounted.clone();
+                    move |update| {
+                        // the component could be dropped while the value is requested
+                        if *mounted.borrow() {
+                            let mut state = state;
+                            state.set(update)
+                        }
+                    }
+                })
+                .await;
+
+                match listen_handle {
+                    Ok(listen_handle) if *mounted.borrow() => {
+                        handle.replace(Some(listen_handle));
+                    }
+                    Ok(_) => {}
+                    Err(why) => log::error!("{why}"),
+                }
+            })
+        }
+    });
+
+    use_drop(move || {
+        mounted.replace(false);
+        handle.take();
+    });
+
+    state.into()
+}
+
+/// Creates a [Resource] of a command binding, which is restarted on invalidation
+///
+/// The resource is restarted every time a signal, that is read in `command`, changes and
+/// every time the results of the command named `command_name` are invalidated. The hook
+/// `use_{command}_resource` generated for commands with the `dioxus` attribute uses this.
+///
+/// ### Example
+///
+/// ```ignore
+/// #[component]
+/// fn Greeting(name: Signal<String>) -> Element {
+///     let greeting = tauri_interop::dioxus::use_command("greet", move || async move {
+///         api::cmd::greet(&name()).await
+///     });
+///
+///     match &*greeting.read() {
+///         Some(greeting) => rsx! { p { "{greeting}" } },
+///         None => rsx! { p { "loading..." } },
+///     }
+/// }
+/// ```
+pub fn use_command<O, Fut>(
+    command_name: &'static str,
+    command: impl FnMut() -> Fut + 'static,
+) -> Resource<O>
+where
+    O: 'static,
+    Fut: Future<Output = O> + 'static,
+{
+    let resource = use_resource(command);
+
+    // the handle is dropped together with the component
+    use_hook(move || {
+        Rc::new(on_invalidate(command_name, move || {
+            let mut resource = resource;
+            resource.restart()
+        }))
+    });
+
+    resource
+}
@@ -1,12 +1,15 @@
-#[cfg(any(feature = "initial_value", doc))]
-use serde::Deserialize;
-use serde::{de::DeserializeOwned, Serialize};
 #[cfg(not(target_family = "wasm"))]
-use tauri::{AppHandle, Error, Wry};
+use std::borrow::Cow;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+#[cfg(not(target_family = "wasm"))]
+use tauri::{AppHandle, Error, EventTarget, Wry};
 
+pub use collection::*;
 #[cfg(not(target_family = "wasm"))]
 #[doc(cfg(not(target_family = "wasm")))]
 pub use emit::*;
+pub use host_event::*;
 #[cfg(any(target_family = "wasm", doc))]
 #[doc(cfg(target_family = "wasm"))]
 pub use listen::*;
@@ -18,6 +21,12 @@ use tauri_interop_macro::{Emit, EmitField, Event, Listen, ListenField};
 #[doc(cfg(not(target_family = "wasm")))]
 mod emit;
 
+/// changes of collection fields, which are emitted as deltas
+mod collection;
+
+/// traits for events emitted by the frontend and listened to by the host
+mod host_event;
+
 /// related generic struct and functions for autogenerated listen functions
 #[cfg(any(target_family = "wasm", doc))]
 #[doc(cfg(target_family = "wasm"))]
@@ -55,19 +64,70 @@ mod listen;
 pub trait Field<P>
 where
     P: Parent,
-    Self::Type: Default + Clone + Serialize + DeserializeOwned + 'static,
+    Self::Type: Clone + Serialize + DeserializeOwned + 'static,
 {
     /// The type of the field
     type Type;
 
     /// The event of the field
+    ///
+    /// Defaults to the path of the field-struct, see [Event] for configuring the name.
     const EVENT_NAME: &'static str;
 
+    /// The events, which are emitted when the field changes
+    ///
+    /// Besides [Self::EVENT_NAME], this contains the delta event of a [CollectionField].
+    const CHANGE_EVENTS: &'static [&'static str] = &[Self::EVENT_NAME];
+
     /// Tries to retrieve the current value from the backend
     #[allow(async_fn_in_trait)]
     #[cfg(any(all(target_family = "wasm", feature = "initial_value"), doc))]
     #[doc(cfg(all(target_family = "wasm", feature = "initial_value")))]
-    async fn get_value() -> Result<Self::Type, EventError>;
+    async fn get_value() -> Result<Self::Type, EventError> {
+        Self::get_revisioned_value()
+            .await
+            .map(|revisioned| revisioned.value)
+    }
+
+    /// Tries to retrieve the current value together with its revision from the backend
+    #[allow(async_fn_in_trait)]
+    #[cfg(any(all(target_family = "wasm", feature = "initial_value"), doc))]
+    #[doc(cfg(all(target_family = "wasm", feature = "initial_value")))]
+    async fn get_revisioned_value() -> Result<Revisioned<Self::Type>, EventError>;
+
+    /// Updates the value in the backend, which emits the new value afterward
+    ///
+    /// Only fields marked with `#[event(writable)]` can be updated, otherwise
+    /// [EventError::NotWritable] is returned.
+    #[allow(async_fn_in_trait)]
+    #[cfg(any(all(target_family = "wasm", feature = "initial_value"), doc))]
+    #[doc(cfg(all(target_family = "wasm", feature = "initial_value")))]
+    async fn set_value(value: Self::Type) -> Result<(), EventError> {
+        drop(value);
+        Err(EventError::NotWritable(Self::EVENT_NAME.into()))
+    }
+
+    /// Subscribes a callback to the events of the field
+    ///
+    /// Fields marked with `#[event(nested)]` compose the events of their nested fields into
+    /// their value, see [ListenHandle::subscribe_nested]. Fields marked with
+    /// `#[event(collection)]` patch their value with its deltas, see
+    /// [ListenHandle::subscribe_collection].
+    #[cfg(any(target_family = "wasm", doc))]
+    #[doc(cfg(target_family = "wasm"))]
+    fn subscribe(
+        target: ListenTarget,
+        callback: impl Fn(Revisioned<Self::Type>) + 'static,
+    ) -> impl std::future::Future<Output = ListenResult> {
+        ListenHandle::subscribe_with_target(Self::EVENT_NAME, target, callback)
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[doc(cfg(not(target_family = "wasm")))]
+    /// Returns the event of the related field together with its value and a new revision
+    ///
+    /// not in wasm available
+    fn revisioned_event(parent: &P) -> (Cow<'static, str>, Revisioned<Self::Type>);
 
     #[cfg(not(target_family = "wasm"))]
     #[doc(cfg(not(target_family = "wasm")))]
@@ -82,14 +142,258 @@ where
     ///
     /// not in wasm available
     fn update(s: &mut P, handle: &AppHandle<Wry>, v: Self::Type) -> Result<(), Error>;
+
+    #[cfg(not(target_family = "wasm"))]
+    #[doc(cfg(not(target_family = "wasm")))]
+    /// Returns the related field for mutation, without emitting its event
+    ///
+    /// not in wasm available
+    fn value_mut(parent: &mut P) -> &mut Self::Type;
+
+    #[cfg(not(target_family = "wasm"))]
+    #[doc(cfg(not(target_family = "wasm")))]
+    /// Mutates the related field in place and emits its event once afterward
+    ///
+    /// not in wasm available
+    fn modify(
+        parent: &mut P,
+        handle: &AppHandle<Wry>,
+        f: impl FnOnce(&mut Self::Type),
+    ) -> Result<(), Error> {
+        f(Self::value_mut(parent));
+        Self::emit(parent, handle)
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[doc(cfg(not(target_family = "wasm")))]
+    /// Updates the related field and emits its event, if the value differs from the current one
+    ///
+    /// Returns `true`, if the value changed and was emitted.
+    ///
+    /// not in wasm available
+    fn update_if_changed(
+        parent: &mut P,
+        handle: &AppHandle<Wry>,
+        v: Self::Type,
+    ) -> Result<bool, Error>
+    where
+        Self::Type: PartialEq,
+    {
+        let value = Self::value_mut(parent);
+        if *value == v {
+            log::trace!(
+                "Skipped event [{}], the value didn't change",
+                Self::EVENT_NAME
+            );
+            return Ok(false);
+        }
+
+        *value = v;
+        Self::emit(parent, handle).map(|_| true)
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[doc(cfg(not(target_family = "wasm")))]
+    /// Emits event of the related field with their value to the given target only
+    ///
+    /// not in wasm available
+    fn emit_to(parent: &P, handle: &AppHandle<Wry>, target: EventTarget) -> Result<(), Error>;
+
+    #[cfg(not(target_family = "wasm"))]
+    #[doc(cfg(not(target_family = "wasm")))]
+    /// Emits event of the related field with their value to all targets matching `filter`
+    ///
+    /// not in wasm available
+    fn emit_filter(
+        parent: &P,
+        handle: &AppHandle<Wry>,
+        filter: impl Fn(&EventTarget) -> bool,
+    ) -> Result<(), Error>;
+
+    #[cfg(not(target_family = "wasm"))]
+    #[doc(cfg(not(target_family = "wasm")))]
+    /// Updates the related field and emit its event to the given target only
+    ///
+    /// not in wasm available
+    fn update_to(
+        s: &mut P,
+        handle: &AppHandle<Wry>,
+        target: EventTarget,
+        v: Self::Type,
+    ) -> Result<(), Error>;
+}
+
+/// A [Field] of a struct marked with `#[event(key = field)]`, of which multiple instances exist
+///
+/// The key of an instance is folded into the event name, so that listeners only receive the
+/// events of a single instance. As tauri rejects event names containing other characters, every
+/// character of the key except alphanumeric characters, `-` and `/` is encoded as `_` followed by
+/// the hex digits of its bytes (see [encode_key]). Listening to the field without a key (e.g.
+/// with `Listen::listen_to`) receives no events, for the same reason it isn't a
+/// [SingleEventField].
+///
+/// ### Example
+///
+/// ```ignore
+/// use tauri_interop::{event::Listen, Event};
+///
+/// #[derive(Event)]
+/// #[event(key = id)]
+/// pub struct Document {
+///     id: u32,
+///     title: String,
+/// }
+///
+/// // host, emits to "my_crate::document::FTitle::1"
+/// fn rename(document: &mut Document, handle: &tauri::AppHandle, title: String) {
+///     document.update::<document::FTitle>(handle, title).expect("emitting failed");
+/// }
+///
+/// // wasm, only receives the title of document 1
+/// async fn listen() {
+///     let _handle = Document::listen_to_instance::<document::FTitle>("1", |title| {
+///         log::info!("document 1 renamed to {title}")
+///     }).await;
+/// }
+/// ```
+pub trait InstanceField<P>: Field<P>
+where
+    P: Parent,
+{
+    /// The event of the field for the instance with the given key
+    fn instance_event_name(key: &str) -> String {
+        format!("{}::{}", Self::EVENT_NAME, encode_key(key))
+    }
+
+    /// Tries to retrieve the current value of the instance with the given key from the backend
+    #[allow(async_fn_in_trait)]
+    #[cfg(any(all(target_family = "wasm", feature = "initial_value"), doc))]
+    #[doc(cfg(all(target_family = "wasm", feature = "initial_value")))]
+    async fn get_instance_value(key: &str) -> Result<Revisioned<Self::Type>, EventError>;
+}
+
+/// A [Field], whose events are the same for every value of its parent
+///
+/// Implemented for every field except an [InstanceField], whose events are emitted per key and
+/// can't be listened to beforehand. Only these fields can invalidate a cached command with
+/// `invalidate_on`.
+#[diagnostic::on_unimplemented(
+    note = "the events of an instance field are emitted per key, so they can't be listened to beforehand"
+)]
+pub trait SingleEventField<P>: Field<P>
+where
+    P: Parent,
+{
+    /// Returns the [Field::CHANGE_EVENTS] of the field
+    ///
+    /// Used by the generated bindings of commands with the `invalidate_on` attribute.
+    fn change_events() -> &'static [&'static str] {
+        Self::CHANGE_EVENTS
+    }
+}
+
+/// Encodes the key of an instance, so that it only contains characters allowed in event names
+///
+/// Alphanumeric characters, `-` and `/` are kept, every other byte is encoded as `_` followed by
+/// its two lowercase hex digits. As `_` and `:` are encoded as well, different keys can't result
+/// in the same event name, neither with each other nor with the delta event of a field.
+pub fn encode_key(key: &str) -> String {
+    key.bytes()
+        .fold(String::with_capacity(key.len()), |mut encoded, byte| {
+            if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'/' {
+                encoded.push(byte as char);
+            } else {
+                encoded.push_str(&format!("_{byte:02x}"));
+            }
+
+            encoded
+        })
+}
+
+/// A field value tagged with a revision
+///
+/// Every emitted field event carries a new revision, which increases monotonically. The getter
+/// of a field returns the revision, which was current when the value was read. Comparing the
+/// revisions allows keeping the newest value, independent of the order it was received in.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Revisioned<T> {
+    /// The revision of the value
+    pub revision: u64,
+    /// The value of the field
+    pub value: T,
+}
+
+/// The event carrying the field events of a `Transaction`, which are received together
+pub(crate) const BATCH_EVENT: &str = "tauri_interop::batch";
+
+/// Returns the first event name, which is contained more than once
+///
+/// Used by `combine_handlers!` to assert during const evaluation, that the fields of the
+/// combined mods don't share an event name.
+#[doc(hidden)]
+#[cfg(not(target_family = "wasm"))]
+pub const fn duplicate_event_name(event_names: &[&[&'static str]]) -> Option<&'static str> {
+    const fn equals(a: &str, b: &str) -> bool {
+        let (a, b) = (a.as_bytes(), b.as_bytes());
+        if a.len() != b.len() {
+            return false;
+        }
+
+        let mut i = 0;
+        while i < a.len() {
+            if a[i] != b[i] {
+                return false;
+            }
+            i += 1;
+        }
+
+        true
+    }
+
+    let mut m = 0;
+    while m < event_names.len() {
+        let mut i = 0;
+        while i < event_names[m].len() {
+            let event_name = event_names[m][i];
+
+            // compares the event name with all following ones
+            let (mut other_m, mut other_i) = (m, i + 1);
+            while other_m < event_names.len() {
+                while other_i < event_names[other_m].len() {
+                    if equals(event_name, event_names[other_m][other_i]) {
+                        return Some(event_name);
+                    }
+                    other_i += 1;
+                }
+                other_m += 1;
+                other_i = 0;
+            }
+
+            i += 1;
+        }
+        m += 1;
+    }
+
+    None
 }
 
 #[cfg(any(feature = "initial_value", doc))]
 #[doc(cfg(feature = "initial_value"))]
 /// General errors that can happen during event exchange
-#[derive(Debug, Serialize, Deserialize, thiserror::Error)]
+#[derive(Debug, Clone, Serialize, Deserialize, thiserror::Error)]
 pub enum EventError {
     /// The given name (struct) is not as tauri::State registered
     #[error("{0} is not as tauri state registered")]
     StateIsNotRegistered(String),
+    /// The getter of the given event didn't respond with a value
+    ///
+    /// Occurs when the getter command isn't registered or the response couldn't be converted.
+    #[error("no value was received for {0}")]
+    ValueUnavailable(String),
+    /// The given event belongs to a field, which isn't marked as writable
+    #[error("{0} is not writable")]
+    NotWritable(String),
+    /// The updated value couldn't be emitted
+    #[error("emitting the updated value failed: {0}")]
+    EmitFailed(String),
 }
@@ -0,0 +1,179 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+#[cfg(not(target_family = "wasm"))]
+use tauri::{AppHandle, Emitter, Error, Wry};
+
+use super::{Field, Parent};
+
+/// A change of a [Collection], which is emitted instead of the whole collection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Delta<K, V> {
+    /// The item was appended, only supported by sequences
+    Push(V),
+    /// The item was inserted at the index or key
+    Insert(K, V),
+    /// The item at the index or key was removed
+    Remove(K),
+    /// All items were removed
+    Clear,
+}
+
+/// A [Delta] of a [CollectionField]
+///
+/// Besides its own revision, the delta carries the revision of the previous change of the
+/// field. A listener, whose copy of the collection is older than the previous change, missed
+/// a change and has to request the whole collection again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevisionedDelta<K, V> {
+    /// The revision of the previous change of the field
+    pub previous: u64,
+    /// The revision of the delta
+    pub revision: u64,
+    /// The change of the field
+    pub delta: Delta<K, V>,
+}
+
+/// A collection, whose changes can be exchanged as [Delta]
+pub trait Collection: Clone + Serialize + DeserializeOwned + 'static {
+    /// The index or key of an item
+    type Key: Clone + Serialize + DeserializeOwned + 'static;
+    /// The type of an item
+    type Item: Clone + Serialize + DeserializeOwned + 'static;
+
+    /// Applies the delta and returns `true`, if it could be applied
+    ///
+    /// A delta can't be applied, when the index is out of bounds, the key is missing or the
+    /// collection doesn't support it.
+    fn apply(&mut self, delta: Delta<Self::Key, Self::Item>) -> bool;
+}
+
+impl<T> Collection for Vec<T>
+where
+    T: Clone + Serialize + DeserializeOwned + 'static,
+{
+    type Key = usize;
+    type Item = T;
+
+    fn apply(&mut self, delta: Delta<usize, T>) -> bool {
+        match delta {
+            Delta::Push(item) => self.push(item),
+            Delta::Insert(index, item) if index <= self.len() => self.insert(index, item),
+            Delta::Remove(index) if index < self.len() => drop(self.remove(index)),
+            Delta::Clear => self.clear(),
+            Delta::Insert(..) | Delta::Remove(_) => return false,
+        }
+
+        true
+    }
+}
+
+impl<K, V, S> Collection for HashMap<K, V, S>
+where
+    K: Clone + Eq + Hash + Serialize + DeserializeOwned + 'static,
+    V: Clone + Serialize + DeserializeOwned + 'static,
+    S: Clone + Default + BuildHasher + 'static,
+{
+    type Key = K;
+    type Item = V;
+
+    fn apply(&mut self, delta: Delta<K, V>) -> bool {
+        match delta {
+            Delta::Insert(key, item) => drop(self.insert(key, item)),
+            Delta::Remove(key) => return self.remove(&key).is_some(),
+            Delta::Clear => self.clear(),
+            Delta::Push(_) => return false,
+        }
+
+        true
+    }
+}
+
+impl<K, V> Collection for BTreeMap<K, V>
+where
+    K: Clone + Ord + Serialize + DeserializeOwned + 'static,
+    V: Clone + Serialize + DeserializeOwned + 'static,
+{
+    type Key = K;
+    type Item = V;
+
+    fn apply(&mut self, delta: Delta<K, V>) -> bool {
+        match delta {
+            Delta::Insert(key, item) => drop(self.insert(key, item)),
+            Delta::Remove(key) => return self.remove(&key).is_some(),
+            Delta::Clear => self.clear(),
+            Delta::Push(_) => return false,
+        }
+
+        true
+    }
+}
+
+/// A [Field] marked with `#[event(collection)]`, whose changes are emitted as [Delta]
+///
+/// Emitting a single change (see `Emit::push`, `Emit::insert`, `Emit::remove` and
+/// `Emit::clear`) only sends the [RevisionedDelta] to [CollectionField::delta_event_name],
+/// while [Field::emit] still sends the whole collection. In wasm, the listeners of the field
+/// hold a copy of the collection, which is updated by the deltas and requested again with the
+/// getter, when a change was missed (see `ListenHandle::subscribe_collection`).
+///
+/// ### Example
+///
+/// ```ignore
+/// use tauri_interop::{event::Emit, Event};
+///
+/// #[derive(Event)]
+/// pub struct Journal {
+///     #[event(collection)]
+///     entries: Vec<String>,
+/// }
+///
+/// // host, only emits the appended entry
+/// fn append(journal: &mut Journal, handle: &tauri::AppHandle, entry: String) {
+///     journal.push::<journal::FEntries>(handle, entry).expect("emitting failed");
+/// }
+/// ```
+pub trait CollectionField<P>: Field<P>
+where
+    P: Parent,
+    Self::Type: Collection,
+{
+    /// The event of the changes of the field
+    fn delta_event_name() -> String {
+        format!("{}::delta", Self::EVENT_NAME)
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    #[doc(cfg(not(target_family = "wasm")))]
+    /// Applies the delta to the field and emits it, if it could be applied
+    ///
+    /// not in wasm available
+    fn apply(
+        parent: &mut P,
+        handle: &AppHandle<Wry>,
+        delta: Delta<<Self::Type as Collection>::Key, <Self::Type as Collection>::Item>,
+    ) -> Result<(), Error> {
+        if !Self::value_mut(parent).apply(delta.clone()) {
+            log::trace!(
+                "Skipped delta of [{}], it can't be applied",
+                Self::EVENT_NAME
+            );
+            return Ok(());
+        }
+
+        let event = Self::delta_event_name();
+        let (previous, revision) = super::next_collection_revision(Self::EVENT_NAME);
+
+        log::trace!("Emitted event [{}]", event);
+
+        handle.emit(
+            &event,
+            RevisionedDelta {
+                previous,
+                revision,
+                delta,
+            },
+        )
+    }
+}
@@ -1,14 +1,56 @@
-use tauri::{AppHandle, Error, Wry};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Error, EventTarget, Runtime, Wry};
 
-use super::Field;
 #[cfg(doc)]
 use super::Listen;
+use super::{Collection, CollectionField, Delta, Field, InstanceField};
+
+pub use transaction::*;
 
 #[cfg(feature = "initial_value")]
 #[doc(cfg(feature = "initial_value"))]
 /// A mod containing functions to acquire a wrapped state manged by tauri
 pub mod state_helper;
 
+/// changes of multiple fields, which are emitted as a single event
+mod transaction;
+
+/// The latest revision of any emitted field event
+static REVISION: AtomicU64 = AtomicU64::new(0);
+
+/// Returns a new revision for a field event that is about to be emitted
+///
+/// See [super::Revisioned] for the purpose of revisions.
+pub fn next_revision() -> u64 {
+    REVISION.fetch_add(1, Ordering::SeqCst) + 1
+}
+
+/// The revision of the latest event of each [CollectionField], keyed by its event name
+static COLLECTION_REVISIONS: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
+
+/// Returns a new revision for an event of a [CollectionField] together with the revision of its
+/// previous event
+///
+/// See [super::RevisionedDelta] for the purpose of the previous revision.
+pub fn next_collection_revision(event: &str) -> (u64, u64) {
+    let mut revisions = COLLECTION_REVISIONS.lock().unwrap();
+    let revision = next_revision();
+    let previous = revisions.insert(event.to_string(), revision);
+
+    (previous.unwrap_or_default(), revision)
+}
+
+/// Returns the revision of the latest emitted field event
+///
+/// The revision has to be acquired before the value of the field is read, so that a value is
+/// never tagged with a revision newer than itself.
+pub fn current_revision() -> u64 {
+    REVISION.load(Ordering::SeqCst)
+}
+
 /// The trait which needs to be implemented for a [Field]
 ///
 /// Conditionally changes between [Listen] and [Emit] or [ManagedEmit]
@@ -23,6 +65,25 @@ pub trait Parent = Emit;
 #[cfg(all(feature = "initial_value", not(doc)))]
 pub trait Parent = ManagedEmit;
 
+/// The way [ManagedEmit] acquires [Self] from the state managed by tauri
+///
+/// [Self]: ManagedEmit
+#[cfg(feature = "initial_value")]
+#[doc(cfg(feature = "initial_value"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StateAccess {
+    /// Acquires the state wrapped in an [RwLock](std::sync::RwLock), otherwise wrapped in a
+    /// [Mutex] and otherwise directly
+    #[default]
+    Detect,
+    /// Acquires the state directly, which can't be mutated
+    Directly,
+    /// Acquires the state wrapped in an [RwLock](std::sync::RwLock)
+    RwLock,
+    /// Acquires the state wrapped in a [Mutex]
+    Mutex,
+}
+
 /// Extension of [Emit] to additionally require [Self] to be managed by tauri
 #[cfg(feature = "initial_value")]
 #[doc(cfg(feature = "initial_value"))]
@@ -30,19 +91,52 @@ pub trait ManagedEmit: Emit
 where
     Self: 'static + Send + Sync,
 {
+    /// The way [Self] is acquired by the default implementations of [ManagedEmit::get_value]
+    /// and [ManagedEmit::update_value]
+    ///
+    /// Set it when [Self] is managed wrapped in a specific way, the getter and setter commands
+    /// of the fields then use the same state.
+    const STATE_ACCESS: StateAccess = StateAccess::Detect;
+
     /// Gets the value of a [Field] from [AppHandle]
     ///
-    /// The default implementation acquires [Self] directly using [state_helper::directly].
-    /// Override the provided method when [Self] is not directly managed. For example,
-    /// this could be the case when the [interior mutability](https://doc.rust-lang.org/reference/interior-mutability.html)
-    /// pattern is used to allow mutation of [Self] while being managed by tauri.
+    /// The default implementation acquires [Self] as given by [ManagedEmit::STATE_ACCESS] using
+    /// [state_helper::acquire]. Override the provided method when [Self] is managed differently,
+    /// for example when it is only accessible through a wrapper of its own.
     ///
     /// Default state acquiring is provided via [state_helper].
     fn get_value<F: Field<Self>>(
         handle: &AppHandle,
         f: impl Fn(&Self) -> F::Type,
     ) -> Option<F::Type> {
-        state_helper::directly::<Self, F>(handle, f)
+        state_helper::acquire::<Self, F>(handle, Self::STATE_ACCESS, f)
+    }
+
+    /// Gets the value of an [InstanceField] from the instance with the given key
+    ///
+    /// Used by the generated getter commands of structs marked with `#[event(key = field)]`.
+    /// The default implementation acquires the instances as [HashMap] wrapped in a [RwLock]
+    /// using [state_helper::instances]. Override the provided method when the instances are
+    /// managed differently.
+    ///
+    /// [HashMap]: std::collections::HashMap
+    /// [RwLock]: std::sync::RwLock
+    fn get_instance_value<F: InstanceField<Self>>(
+        handle: &AppHandle,
+        key: &str,
+        f: impl Fn(&Self) -> F::Type,
+    ) -> Option<F::Type> {
+        state_helper::instances::<Self, F>(handle, key, f)
+    }
+
+    /// Mutates [Self] managed by tauri with `f`
+    ///
+    /// Used by the generated setter commands of writable fields. The default implementation
+    /// acquires [Self] as given by [ManagedEmit::STATE_ACCESS] using [state_helper::acquire_mut],
+    /// a directly managed state can't be mutated. Override the provided method together with
+    /// [ManagedEmit::get_value], when [Self] is managed differently.
+    fn update_value<R>(handle: &AppHandle, f: impl FnOnce(&mut Self) -> R) -> Option<R> {
+        state_helper::acquire_mut(handle, Self::STATE_ACCESS, f)
     }
 }
 
@@ -131,4 +225,259 @@ pub trait Emit: Sized {
     ) -> Result<(), Error>
     where
         Self: Parent;
+
+    /// Update a single field and emit it afterward, unless the value didn't change
+    ///
+    /// Returns `true`, if the value changed and was emitted.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use tauri_interop::{command::TauriAppHandle, event::Emit, Event};
+    ///
+    /// #[derive(Default, Event)]
+    /// pub struct Test {
+    ///     foo: String,
+    ///     pub bar: bool,
+    /// }
+    ///
+    /// #[cfg(feature = "initial_value")]
+    /// impl tauri_interop::event::ManagedEmit for Test {}
+    ///
+    /// #[tauri_interop::command]
+    /// fn emit_bar(handle: TauriAppHandle) {
+    ///     // the default value of bar is false, so nothing is emitted
+    ///     let emitted = Test::default().update_if_changed::<test::FBar>(&handle, false).expect("emitting failed");
+    ///     assert!(!emitted);
+    /// }
+    ///
+    /// fn main() {}
+    /// ```
+    fn update_if_changed<F: Field<Self>>(
+        &mut self,
+        handle: &AppHandle<Wry>,
+        field: F::Type,
+    ) -> Result<bool, Error>
+    where
+        Self: Parent,
+        F::Type: PartialEq,
+    {
+        F::update_if_changed(self, handle, field)
+    }
+
+    /// Mutate a single field in place and emit it once afterward
+    ///
+    /// Avoids cloning a large field out of [Self] to update it.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use tauri_interop::{command::TauriAppHandle, event::Emit, Event};
+    ///
+    /// #[derive(Default, Event)]
+    /// pub struct Test {
+    ///     foo: String,
+    ///     pub bar: bool,
+    /// }
+    ///
+    /// #[cfg(feature = "initial_value")]
+    /// impl tauri_interop::event::ManagedEmit for Test {}
+    ///
+    /// #[tauri_interop::command]
+    /// fn append_foo(handle: TauriAppHandle) {
+    ///     Test::default().modify::<test::FFoo>(&handle, |foo| foo.push('!')).expect("emitting failed");
+    /// }
+    ///
+    /// fn main() {}
+    /// ```
+    fn modify<F: Field<Self>>(
+        &mut self,
+        handle: &AppHandle<Wry>,
+        f: impl FnOnce(&mut F::Type),
+    ) -> Result<(), Error>
+    where
+        Self: Parent,
+    {
+        F::modify(self, handle, f)
+    }
+
+    /// Change multiple fields and emit them afterward as a batch
+    ///
+    /// See [Transaction] for how the events are received.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use tauri_interop::{command::TauriAppHandle, event::Emit, Event};
+    ///
+    /// #[derive(Default, Event)]
+    /// pub struct Test {
+    ///     foo: String,
+    ///     pub bar: bool,
+    /// }
+    ///
+    /// #[cfg(feature = "initial_value")]
+    /// impl tauri_interop::event::ManagedEmit for Test {}
+    ///
+    /// #[tauri_interop::command]
+    /// fn emit_both(handle: TauriAppHandle) {
+    ///     Test::default()
+    ///         .transaction(&handle, |tx| {
+    ///             tx.set::<test::FFoo>("foo".into());
+    ///             tx.set::<test::FBar>(true);
+    ///         })
+    ///         .expect("emitting failed");
+    /// }
+    ///
+    /// fn main() {}
+    /// ```
+    fn transaction(
+        &mut self,
+        handle: &AppHandle<impl Runtime>,
+        f: impl FnOnce(&mut Transaction<Self>),
+    ) -> Result<(), Error>
+    where
+        Self: Parent,
+    {
+        let mut transaction = Transaction::new(self);
+        f(&mut transaction);
+        transaction.emit(handle)
+    }
+
+    /// Emit a single field event to the given target only
+    ///
+    /// The target can be a label of a window or webview, or any other [EventTarget].
+    /// In wasm, only listeners registered for the target or for any target receive the event.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use tauri_interop::{command::TauriAppHandle, event::Emit, Event};
+    ///
+    /// #[derive(Default, Event)]
+    /// pub struct Test {
+    ///     foo: String,
+    ///     pub bar: bool,
+    /// }
+    ///
+    /// #[cfg(feature = "initial_value")]
+    /// impl tauri_interop::event::ManagedEmit for Test {}
+    ///
+    /// #[tauri_interop::command]
+    /// fn emit_bar(handle: TauriAppHandle) {
+    ///     Test::default().emit_to::<test::FFoo>(&handle, "main").expect("emitting failed");
+    /// }
+    ///
+    /// fn main() {}
+    /// ```
+    fn emit_to<F: Field<Self>>(
+        &self,
+        handle: &AppHandle<Wry>,
+        target: impl Into<EventTarget>,
+    ) -> Result<(), Error>
+    where
+        Self: Parent,
+    {
+        F::emit_to(self, handle, target.into())
+    }
+
+    /// Emit a single field event to all targets matching `filter`
+    fn emit_filter<F: Field<Self>>(
+        &self,
+        handle: &AppHandle<Wry>,
+        filter: impl Fn(&EventTarget) -> bool,
+    ) -> Result<(), Error>
+    where
+        Self: Parent,
+    {
+        F::emit_filter(self, handle, filter)
+    }
+
+    /// Update a single field and emit it afterward to the given target only
+    ///
+    /// See [Emit::emit_to] for the possible targets.
+    fn update_to<F: Field<Self>>(
+        &mut self,
+        handle: &AppHandle<Wry>,
+        target: impl Into<EventTarget>,
+        field: F::Type,
+    ) -> Result<(), Error>
+    where
+        Self: Parent,
+    {
+        F::update_to(self, handle, target.into(), field)
+    }
+
+    /// Append an item to a collection field and emit only the [Delta]
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use tauri_interop::{command::TauriAppHandle, event::Emit, Event};
+    ///
+    /// #[derive(Default, Event)]
+    /// pub struct Test {
+    ///     #[event(collection)]
+    ///     entries: Vec<String>,
+    /// }
+    ///
+    /// #[cfg(feature = "initial_value")]
+    /// impl tauri_interop::event::ManagedEmit for Test {}
+    ///
+    /// #[tauri_interop::command]
+    /// fn append(handle: TauriAppHandle) {
+    ///     Test::default().push::<test::FEntries>(&handle, "entry".into()).expect("emitting failed");
+    /// }
+    ///
+    /// fn main() {}
+    /// ```
+    fn push<F: CollectionField<Self>>(
+        &mut self,
+        handle: &AppHandle<Wry>,
+        item: <F::Type as Collection>::Item,
+    ) -> Result<(), Error>
+    where
+        Self: Parent,
+        F::Type: Collection,
+    {
+        F::apply(self, handle, Delta::Push(item))
+    }
+
+    /// Insert an item at the index or key of a collection field and emit only the [Delta]
+    ///
+    /// See [CollectionField::apply] for changes, which can't be applied.
+    fn insert<F: CollectionField<Self>>(
+        &mut self,
+        handle: &AppHandle<Wry>,
+        key: <F::Type as Collection>::Key,
+        item: <F::Type as Collection>::Item,
+    ) -> Result<(), Error>
+    where
+        Self: Parent,
+        F::Type: Collection,
+    {
+        F::apply(self, handle, Delta::Insert(key, item))
+    }
+
+    /// Remove the item at the index or key of a collection field and emit only the [Delta]
+    fn remove<F: CollectionField<Self>>(
+        &mut self,
+        handle: &AppHandle<Wry>,
+        key: <F::Type as Collection>::Key,
+    ) -> Result<(), Error>
+    where
+        Self: Parent,
+        F::Type: Collection,
+    {
+        F::apply(self, handle, Delta::Remove(key))
+    }
+
+    /// Remove all items of a collection field and emit only the [Delta]
+    fn clear<F: CollectionField<Self>>(&mut self, handle: &AppHandle<Wry>) -> Result<(), Error>
+    where
+        Self: Parent,
+        F::Type: Collection,
+    {
+        F::apply(self, handle, Delta::Clear)
+    }
 }
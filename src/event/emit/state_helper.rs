@@ -1,11 +1,52 @@
 use super::*;
+use std::collections::HashMap;
 use std::sync::{Mutex, RwLock};
+use tauri::Runtime;
 
-/// Acquires the state directly
+/// Acquires the state the way given by `access`
 ///
 /// Default usage when [ManagedEmit::get_value] isn't overridden.
+pub fn acquire<P: ManagedEmit, F: Field<P>>(
+    handle: &AppHandle<impl Runtime>,
+    access: StateAccess,
+    f: impl Fn(&P) -> F::Type,
+) -> Option<F::Type> {
+    match access {
+        StateAccess::Detect if is_managed::<RwLock<P>>(handle) => rwlock::<P, F>(handle, f),
+        StateAccess::Detect if is_managed::<Mutex<P>>(handle) => mutex::<P, F>(handle, f),
+        StateAccess::Detect | StateAccess::Directly => directly::<P, F>(handle, f),
+        StateAccess::RwLock => rwlock::<P, F>(handle, f),
+        StateAccess::Mutex => mutex::<P, F>(handle, f),
+    }
+}
+
+/// Acquires the state for mutation the way given by `access`
+///
+/// Default usage when [ManagedEmit::update_value] isn't overridden. A directly managed state
+/// can't be mutated, so [None] is returned for it.
+pub fn acquire_mut<P: ManagedEmit, R>(
+    handle: &AppHandle<impl Runtime>,
+    access: StateAccess,
+    f: impl FnOnce(&mut P) -> R,
+) -> Option<R> {
+    match access {
+        StateAccess::Detect if is_managed::<RwLock<P>>(handle) => rwlock_mut(handle, f),
+        StateAccess::Detect if is_managed::<Mutex<P>>(handle) => mutex_mut(handle, f),
+        StateAccess::Detect | StateAccess::Directly => None,
+        StateAccess::RwLock => rwlock_mut(handle, f),
+        StateAccess::Mutex => mutex_mut(handle, f),
+    }
+}
+
+fn is_managed<T: Send + Sync + 'static>(handle: &AppHandle<impl Runtime>) -> bool {
+    use tauri::Manager;
+
+    handle.try_state::<T>().is_some()
+}
+
+/// Acquires the state directly
 pub fn directly<P: ManagedEmit, F: Field<P>>(
-    handle: &AppHandle,
+    handle: &AppHandle<impl Runtime>,
     f: impl Fn(&P) -> F::Type,
 ) -> Option<F::Type> {
     use tauri::Manager;
@@ -16,7 +57,7 @@ pub fn directly<P: ManagedEmit, F: Field<P>>(
 
 /// Acquires the state wrapped in an [Option]
 pub fn option<P: ManagedEmit, F: Field<P>>(
-    handle: &AppHandle,
+    handle: &AppHandle<impl Runtime>,
     f: impl Fn(&P) -> F::Type,
 ) -> Option<F::Type> {
     use tauri::Manager;
@@ -27,7 +68,7 @@ pub fn option<P: ManagedEmit, F: Field<P>>(
 
 /// Acquires the state wrapped in an [RwLock]
 pub fn rwlock<P: ManagedEmit, F: Field<P>>(
-    handle: &AppHandle,
+    handle: &AppHandle<impl Runtime>,
     f: impl Fn(&P) -> F::Type,
 ) -> Option<F::Type> {
     use tauri::Manager;
@@ -39,7 +80,7 @@ pub fn rwlock<P: ManagedEmit, F: Field<P>>(
 
 /// Acquires the state wrapped in a [Mutex]
 pub fn mutex<P: ManagedEmit, F: Field<P>>(
-    handle: &AppHandle,
+    handle: &AppHandle<impl Runtime>,
     f: impl Fn(&P) -> F::Type,
 ) -> Option<F::Type> {
     use tauri::Manager;
@@ -48,3 +89,43 @@ pub fn mutex<P: ManagedEmit, F: Field<P>>(
     let state = state.lock().ok()?;
     Some(f(&state))
 }
+
+/// Acquires the state wrapped in an [RwLock] for mutation
+pub fn rwlock_mut<P: ManagedEmit, R>(
+    handle: &AppHandle<impl Runtime>,
+    f: impl FnOnce(&mut P) -> R,
+) -> Option<R> {
+    use tauri::Manager;
+
+    let state = handle.try_state::<RwLock<P>>()?;
+    let mut state = state.write().ok()?;
+    Some(f(&mut state))
+}
+
+/// Acquires the state wrapped in a [Mutex] for mutation
+pub fn mutex_mut<P: ManagedEmit, R>(
+    handle: &AppHandle<impl Runtime>,
+    f: impl FnOnce(&mut P) -> R,
+) -> Option<R> {
+    use tauri::Manager;
+
+    let state = handle.try_state::<Mutex<P>>()?;
+    let mut state = state.lock().ok()?;
+    Some(f(&mut state))
+}
+
+/// Acquires the instance with the given key from a [HashMap] wrapped in an [RwLock]
+///
+/// Default usage when [ManagedEmit::get_instance_value] isn't overridden. The instances are
+/// expected to be stored by the string representation of their key.
+pub fn instances<P: ManagedEmit, F: InstanceField<P>>(
+    handle: &AppHandle<impl Runtime>,
+    key: &str,
+    f: impl Fn(&P) -> F::Type,
+) -> Option<F::Type> {
+    use tauri::Manager;
+
+    let state = handle.try_state::<RwLock<HashMap<String, P>>>()?;
+    let state = state.read().ok()?;
+    Some(f(state.get(key)?))
+}
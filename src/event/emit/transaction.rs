@@ -0,0 +1,134 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Error, Runtime};
+
+use super::Parent;
+use crate::event::{Field, BATCH_EVENT};
+
+type ToBatchEvent<P> = fn(&P) -> Result<BatchEvent, Error>;
+
+/// The field events of a [Transaction], which are received together
+#[derive(Clone, Serialize)]
+struct Batch {
+    events: Vec<BatchEvent>,
+}
+
+/// A single field event of a [Batch], holding the payload as it is emitted by [Field::emit]
+#[derive(Clone, Serialize)]
+struct BatchEvent {
+    event: String,
+    payload: serde_json::Value,
+}
+
+/// Changes of multiple fields, which are emitted together
+///
+/// The fields are changed right away, but their events are only emitted after all changes were
+/// applied (see `Emit::transaction`). In wasm, the subscribers of the fields (see
+/// `ListenHandle::subscribe`) receive the whole batch at once, before any other event is handled.
+///
+/// The batch is emitted as the event `tauri_interop::batch`, followed by the event of each
+/// field. Listeners outside of tauri-interop, like listeners on the host or javascript
+/// listeners, and listeners registered with `ListenHandle::register` receive the field events
+/// one by one. The subscribers skip the field events, which they already received in the batch.
+///
+/// ### Example
+///
+/// ```ignore
+/// use tauri_interop::{event::Emit, Event};
+///
+/// #[derive(Event)]
+/// pub struct Player {
+///     title: String,
+///     position: u32,
+///     playing: bool,
+/// }
+///
+/// // host, emits all three fields as one batch
+/// fn play(player: &mut Player, handle: &tauri::AppHandle, title: String) {
+///     player
+///         .transaction(handle, |tx| {
+///             tx.set::<player::FTitle>(title);
+///             tx.set::<player::FPosition>(0);
+///             tx.set::<player::FPlaying>(true);
+///         })
+///         .expect("emitting failed");
+/// }
+/// ```
+pub struct Transaction<'a, P> {
+    parent: &'a mut P,
+    /// The changed fields by their event name, in the order they were changed first
+    changes: Vec<(&'static str, ToBatchEvent<P>)>,
+}
+
+impl<'a, P: Parent> Transaction<'a, P> {
+    pub(crate) fn new(parent: &'a mut P) -> Self {
+        Self {
+            parent,
+            changes: Vec::new(),
+        }
+    }
+
+    /// Returns the parent including the changes applied so far
+    pub fn parent(&self) -> &P {
+        self.parent
+    }
+
+    /// Sets the value of a field, which is emitted with the transaction
+    pub fn set<F: Field<P>>(&mut self, value: F::Type) -> &mut Self {
+        *F::value_mut(self.parent) = value;
+        self.changed::<F>()
+    }
+
+    /// Mutates a field in place, which is emitted with the transaction
+    pub fn modify<F: Field<P>>(&mut self, f: impl FnOnce(&mut F::Type)) -> &mut Self {
+        f(F::value_mut(self.parent));
+        self.changed::<F>()
+    }
+
+    /// Records the field, so that it is emitted once, regardless how often it was changed
+    fn changed<F: Field<P>>(&mut self) -> &mut Self {
+        if !self
+            .changes
+            .iter()
+            .any(|(event, _)| *event == F::EVENT_NAME)
+        {
+            self.changes.push((F::EVENT_NAME, |parent| {
+                let (event, payload) = F::revisioned_event(parent);
+                Ok(BatchEvent {
+                    event: event.into_owned(),
+                    payload: serde_json::to_value(payload)?,
+                })
+            }));
+        }
+
+        self
+    }
+
+    /// Emits the events of all changed fields as a single [Batch], followed by each event
+    pub(crate) fn emit(self, handle: &AppHandle<impl Runtime>) -> Result<(), Error> {
+        if self.changes.is_empty() {
+            return Ok(());
+        }
+
+        let events = self
+            .changes
+            .iter()
+            .map(|(_, event)| event(self.parent))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        log::trace!(
+            "Emitted batch of [{}]",
+            events
+                .iter()
+                .map(|event| event.event.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        let batch = Batch { events };
+        handle.emit(BATCH_EVENT, &batch)?;
+        batch
+            .events
+            .into_iter()
+            .try_for_each(|BatchEvent { event, payload }| handle.emit(&event, payload))
+    }
+}
@@ -0,0 +1,105 @@
+use serde::{de::DeserializeOwned, Serialize};
+#[cfg(not(target_family = "wasm"))]
+use tauri::{EventId, Listener, Runtime};
+#[cfg(any(target_family = "wasm", doc))]
+use wasm_bindgen::JsValue;
+
+/// Trait of a struct, whose fields are emitted by the frontend and listened to by the host
+///
+/// Derived by [Event](tauri_interop_macro::Event) when the struct is marked with
+/// `#[event(direction = "to_host")]`.
+pub trait HostEvent: Sized {
+    /// Emit all field events to the host
+    ///
+    /// not in the host available
+    #[allow(async_fn_in_trait)]
+    #[cfg(any(target_family = "wasm", doc))]
+    #[doc(cfg(target_family = "wasm"))]
+    async fn emit_all(&self) -> Result<(), HostEmitError>;
+}
+
+/// Trait defining a field of a [HostEvent], emitted by the frontend and listened to by the host
+///
+/// The field-structs are generated and named the same way as the ones of a [super::Field].
+///
+/// ### Example
+///
+/// ```ignore
+/// use tauri_interop::{event::HostField, Event};
+///
+/// #[derive(Event)]
+/// #[event(direction = "to_host")]
+/// pub struct Editor {
+///     cursor: usize,
+/// }
+///
+/// // wasm
+/// async fn moved(cursor: usize) {
+///     editor::FCursor::emit(cursor).await.expect("emitting failed");
+/// }
+///
+/// // host
+/// fn setup(app: &tauri::App) {
+///     editor::FCursor::listen(app, |cursor| log::info!("cursor at {cursor}"));
+/// }
+/// ```
+pub trait HostField<P>
+where
+    P: HostEvent,
+    Self::Type: Clone + Serialize + DeserializeOwned + 'static,
+{
+    /// The type of the field
+    type Type;
+
+    /// The event of the field
+    const EVENT_NAME: &'static str;
+
+    /// Emits the event of the field with the given value to the host
+    ///
+    /// not in the host available
+    #[allow(async_fn_in_trait)]
+    #[cfg(any(target_family = "wasm", doc))]
+    #[doc(cfg(target_family = "wasm"))]
+    async fn emit(value: Self::Type) -> Result<(), HostEmitError> {
+        let payload = value
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .map_err(|why| HostEmitError::Serialization(why.to_string()))?;
+
+        crate::command::bindings::emit(Self::EVENT_NAME, payload)
+            .await
+            .map(|_| ())
+            .map_err(HostEmitError::PromiseFailed)
+    }
+
+    /// Registers a callback for the event of the field and returns the id of the listener
+    ///
+    /// The listener can be removed with [Listener::unlisten].
+    ///
+    /// not in wasm available
+    #[cfg(not(target_family = "wasm"))]
+    #[doc(cfg(not(target_family = "wasm")))]
+    fn listen<R: Runtime>(
+        listener: &impl Listener<R>,
+        callback: impl Fn(Self::Type) + Send + 'static,
+    ) -> EventId {
+        listener.listen(Self::EVENT_NAME, move |event| {
+            match serde_json::from_str(event.payload()) {
+                Ok(value) => callback(value),
+                Err(why) => log::error!("payload of {} is invalid: {why}", Self::EVENT_NAME),
+            }
+        })
+    }
+}
+
+/// Errors that can occur when emitting a [HostField] to the host
+#[cfg(any(target_family = "wasm", doc))]
+#[doc(cfg(target_family = "wasm"))]
+#[derive(Debug, thiserror::Error)]
+pub enum HostEmitError {
+    /// The value couldn't be serialized
+    #[error("The value couldn't be serialized: {0}")]
+    Serialization(String),
+    /// The promise given by tauri's emit failed to resolve
+    #[error("The promise to emit the event failed: {0:?}")]
+    PromiseFailed(JsValue),
+}
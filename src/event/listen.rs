@@ -1,14 +1,35 @@
+use std::any::Any;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::rc::Rc;
+
 use js_sys::Function;
 #[cfg(feature = "leptos")]
 use leptos::prelude::*;
+#[cfg(all(feature = "leptos", feature = "initial_value"))]
+use leptos::reactive::wrappers::write::SignalSetter;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use wasm_bindgen::{closure::Closure, JsCast, JsValue};
 
-use crate::command::bindings::listen;
+use crate::command::bindings::{get_current_window, listen, listen_with_options};
+
+pub use nested::*;
+pub use observable::*;
 
-use super::Field;
+#[cfg(feature = "initial_value")]
+use super::EventError;
 #[cfg(doc)]
 use super::{Emit, Parent};
+use super::{Field, InstanceField, Revisioned};
+
+/// application of the deltas of collection fields
+mod collection;
+/// composition of nested fields into the value of their parent field
+mod nested;
+/// framework independent observation of field values
+mod observable;
+/// shared listeners, multiplexed to all subscribers of an event
+mod registry;
 
 /// The trait which needs to be implemented for a [Field]
 ///
@@ -27,7 +48,7 @@ pub struct Payload<T> {
 }
 
 /// Errors that can occur during registering the callback in [ListenHandle::register]
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, Clone, thiserror::Error)]
 pub enum ListenError {
     /// The promised given by [listen] failed to resolve
     #[error("The promise to register the listener failed: {0:?}")]
@@ -38,16 +59,73 @@ pub enum ListenError {
     NotAFunction(JsValue),
 }
 
+/// The events a listener receives, depending on the target they were emitted to
+///
+/// Events emitted to any target (see `Emit::emit`) are received regardless of the target
+/// of the listener, while events emitted to a specific target (see `Emit::emit_to`) are only
+/// received by the listeners of that target.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum ListenTarget {
+    /// Receives every event, independent of its target
+    #[default]
+    Any,
+    /// Receives the events targeting the window or webview the frontend is running in
+    CurrentWindow,
+    /// Receives the events targeting the window or webview with the given label
+    Label(String),
+}
+
+impl ListenTarget {
+    /// The options passed to tauri's listen function, [None] when listening to any target
+    fn options(&self) -> Option<JsValue> {
+        #[derive(Serialize)]
+        #[serde(tag = "kind")]
+        enum Target {
+            AnyLabel { label: String },
+        }
+
+        #[derive(Serialize)]
+        struct Options {
+            target: Target,
+        }
+
+        let label = match self {
+            Self::Any => return None,
+            Self::CurrentWindow => js_sys::Reflect::get(&get_current_window(), &"label".into())
+                .ok()
+                .and_then(|label| label.as_string())
+                .unwrap_or_default(),
+            Self::Label(label) => label.clone(),
+        };
+
+        let options = Options {
+            target: Target::AnyLabel { label },
+        };
+        serde_wasm_bindgen::to_value(&options).ok()
+    }
+}
+
 /// Handle which holds the function to detach the listener and the correlated callback
 pub struct ListenHandle {
     /// The callback which is invoked for the registered event
     ///
-    /// The callback will get detached, when the handle is dropped. Alternatively it can
-    /// also be given to the js runtime (see [Closure] `into_js_value`/`forget`). This isn't
-    /// recommended because this will leak memory by default.
+    /// The callback will get detached, when the handle is dropped. Only the handles returned
+    /// by [ListenHandle::register] hold a closure, the handles of subscribers (see
+    /// [ListenHandle::subscribe]) share the closure of their listener and hold [None]. Taking
+    /// the closure doesn't keep the listener attached, use [ListenHandle::forget] instead.
     pub closure: Option<Closure<dyn Fn(JsValue)>>,
-    event: &'static str,
-    detach_fn: Function,
+    event: Cow<'static, str>,
+    detach: Detach,
+}
+
+/// The way a [ListenHandle] is detached from its event
+enum Detach {
+    /// The listener is registered directly, calling the function detaches it
+    Listener(Function),
+    /// The handle is a subscriber of a shared listener, dropping it unsubscribes
+    Subscriber(Box<dyn Any>),
+    /// The handle was detached already
+    Detached,
 }
 
 impl Drop for ListenHandle {
@@ -58,10 +136,27 @@ impl Drop for ListenHandle {
 
 impl ListenHandle {
     /// Registers a given event with the correlation callback and returns a [ListenResult]
-    pub async fn register<T>(event: &'static str, callback: impl Fn(T) + 'static) -> ListenResult
+    pub async fn register<T>(
+        event: impl Into<Cow<'static, str>>,
+        callback: impl Fn(T) + 'static,
+    ) -> ListenResult
     where
         T: DeserializeOwned,
     {
+        Self::register_with_target(event, ListenTarget::Any, callback).await
+    }
+
+    /// Registers a given event for the given target with the correlation callback and returns
+    /// a [ListenResult]
+    pub async fn register_with_target<T>(
+        event: impl Into<Cow<'static, str>>,
+        target: ListenTarget,
+        callback: impl Fn(T) + 'static,
+    ) -> ListenResult
+    where
+        T: DeserializeOwned,
+    {
+        let event = event.into();
         let closure = Closure::new(move |value| {
             let payload: Payload<T> = serde_wasm_bindgen::from_value(value)
                 .map_err(|why| log::error!("{why:?}"))
@@ -70,8 +165,11 @@ impl ListenHandle {
             callback(payload.payload)
         });
 
-        let detach_fn = listen(event, &closure)
-            .await
+        let registration = match target.options() {
+            Some(options) => listen_with_options(&event, &closure, options).await,
+            None => listen(&event, &closure).await,
+        };
+        let detach_fn = registration
             .map_err(ListenError::PromiseFailed)?
             .dyn_into()
             .map_err(ListenError::NotAFunction)?;
@@ -80,23 +178,42 @@ impl ListenHandle {
         Ok(ListenHandle {
             event,
             closure,
-            detach_fn,
+            detach: Detach::Listener(detach_fn),
         })
     }
 
+    /// Keeps the callback attached to the event, without holding the handle
+    ///
+    /// The callback can't be detached afterward. This isn't recommended, because the callback
+    /// and its listener leak memory.
+    pub fn forget(self) {
+        log::trace!("Forgetting the handle of {}", self.event);
+        std::mem::forget(self)
+    }
+
     /// Detaches the callback from the registered event
     pub fn detach_listen(&mut self) {
-        log::trace!("Detaching listener for {}", self.event);
+        match std::mem::replace(&mut self.detach, Detach::Detached) {
+            Detach::Listener(detach_fn) => {
+                log::trace!("Detaching listener for {}", self.event);
 
-        self.detach_fn
-            .apply(&JsValue::null(), &js_sys::Array::new())
-            .unwrap();
+                detach_fn
+                    .apply(&JsValue::null(), &js_sys::Array::new())
+                    .unwrap();
+            }
+            Detach::Subscriber(subscriber) => {
+                log::trace!("Unsubscribing from {}", self.event);
+                drop(subscriber)
+            }
+            Detach::Detached => {}
+        }
     }
 
     /// Registers a given event and binds a returned signal to these event changes
     ///
     /// Providing [None] will unwrap into the default value. When feature `initial_value`
-    /// is enabled [None] will try to get the value from tauri.
+    /// is enabled [None] will try to get the value from tauri. The listener is registered
+    /// before the value is requested, so that always the value with the newest revision is kept.
     ///
     /// Internally it stores a created [ListenHandle] for `event` in a [leptos::prelude::RwSignal] to hold it in
     /// scope, while it is used in a leptos [component](https://docs.rs/leptos_macro/0.5.2/leptos_macro/attr.component.html)
@@ -107,42 +224,286 @@ impl ListenHandle {
     ) -> ReadSignal<<F as Field<P>>::Type, LocalStorage>
     where
         P: Parent,
+        F::Type: Default,
     {
-        #[cfg(any(all(target_family = "wasm", feature = "initial_value")))]
+        #[cfg(feature = "initial_value")]
+        let get_value = || F::get_revisioned_value();
+        #[cfg(not(feature = "initial_value"))]
+        let get_value = || {};
+
+        let subscribe = |callback| F::subscribe(ListenTarget::Any, callback);
+
+        Self::register_signal(F::EVENT_NAME.into(), initial_value, subscribe, get_value).0
+    }
+
+    /// Registers a given event and binds a returned signal to the instance of a [InstanceField]
+    ///
+    /// Behaves like [ListenHandle::use_register], but only receives the events of the instance
+    /// with the given key and requests the value of that instance.
+    #[cfg(feature = "leptos")]
+    #[doc(cfg(feature = "leptos"))]
+    pub fn use_register_instance<P, F: InstanceField<P>>(
+        key: impl Into<String>,
+        initial_value: Option<F::Type>,
+    ) -> ReadSignal<<F as Field<P>>::Type, LocalStorage>
+    where
+        P: Parent,
+        F::Type: Default,
+    {
+        let key = key.into();
+        let event = F::instance_event_name(&key);
+        #[cfg(feature = "initial_value")]
+        let get_value = move || async move { F::get_instance_value(&key).await };
+        #[cfg(not(feature = "initial_value"))]
+        let get_value = || {};
+
+        let subscribe = {
+            let event = event.clone();
+            |callback| ListenHandle::subscribe(event, callback)
+        };
+
+        Self::register_signal(event.into(), initial_value, subscribe, get_value).0
+    }
+
+    /// Binds a signal to `event`, which is initialized by `get_value` if no value is provided
+    ///
+    /// The callback updating the signal is subscribed to `event` with `subscribe`.
+    #[cfg(feature = "leptos")]
+    fn register_signal<
+        T,
+        S: std::future::Future<Output = ListenResult>,
+        #[cfg(feature = "initial_value")] Fut: std::future::Future<Output = Result<Revisioned<T>, EventError>>,
+    >(
+        event: Cow<'static, str>,
+        initial_value: Option<T>,
+        subscribe: impl FnOnce(Box<dyn Fn(Revisioned<T>)>) -> S + 'static,
+        #[cfg(feature = "initial_value")] get_value: impl FnOnce() -> Fut + 'static,
+        #[cfg(not(feature = "initial_value"))] get_value: impl FnOnce(),
+    ) -> (ReadSignal<T, LocalStorage>, WriteSignal<T, LocalStorage>)
+    where
+        T: DeserializeOwned + Clone + Default + 'static,
+    {
+        #[cfg(feature = "initial_value")]
         let acquire_initial_value = initial_value.is_none();
+        #[cfg(not(feature = "initial_value"))]
+        drop(get_value);
         let (signal, set_signal) = signal_local(initial_value.unwrap_or_default());
 
         // creating this signal in a leptos component holds the value in scope, and drops it automatically
         let handle = RwSignal::new_local(None);
         leptos::task::spawn_local(async move {
-            #[cfg(any(all(target_family = "wasm", feature = "initial_value")))]
+            let revision = RevisionTracker::default();
+
+            // the listener is registered first, so that no update is missed while acquiring the value
+            let listen_handle = subscribe(Box::new({
+                let revision = revision.clone();
+                move |update: Revisioned<T>| {
+                    log::trace!("update for {event}");
+                    if revision.accept(update.revision) {
+                        set_signal.set(update.value)
+                    }
+                }
+            }))
+            .await
+            .unwrap();
+
+            // it could be that the component doesn't live long enough, so we just try to set it
+            handle.try_set(Some(listen_handle));
+
+            #[cfg(feature = "initial_value")]
             if acquire_initial_value {
-                match F::get_value().await {
-                    Ok(value) => set_signal.set(value),
+                match get_value().await {
+                    // an emitted value could be newer than the requested one
+                    Ok(initial) if revision.accept(initial.revision) => {
+                        set_signal.try_set(initial.value);
+                    }
+                    Ok(_) => {}
                     Err(why) => log::error!("{why}"),
                 }
             }
+        });
+
+        (signal, set_signal)
+    }
+
+    /// Registers a given event and binds a returned signal and setter to the field
+    ///
+    /// The signal behaves like the one of [ListenHandle::use_register]. Setting a value updates
+    /// the signal right away and the field in the backend using [Field::set_value], which
+    /// requires the field to be marked with `#[event(writable)]`.
+    #[cfg(all(feature = "leptos", feature = "initial_value"))]
+    #[doc(cfg(all(feature = "leptos", feature = "initial_value")))]
+    pub fn use_register_writable<P, F: Field<P>>(
+        initial_value: Option<F::Type>,
+    ) -> (
+        ReadSignal<F::Type, LocalStorage>,
+        SignalSetter<F::Type, LocalStorage>,
+    )
+    where
+        P: Parent,
+        F::Type: Default,
+    {
+        let (signal, set_signal) = Self::register_signal(
+            F::EVENT_NAME.into(),
+            initial_value,
+            |callback| F::subscribe(ListenTarget::Any, callback),
+            || F::get_revisioned_value(),
+        );
+        let setter = SignalSetter::map(move |value: F::Type| {
+            set_signal.set(value.clone());
+            leptos::task::spawn_local(async move {
+                if let Err(why) = F::set_value(value).await {
+                    log::error!("{why}")
+                }
+            });
+        });
+
+        (signal, setter)
+    }
 
-            let listen_handle = ListenHandle::register(F::EVENT_NAME, move |value: F::Type| {
+    /// Subscribes to a [Field] and reports its [FieldState] to `callback`
+    ///
+    /// The state is [FieldState::Loading] until a value is received. When feature `initial_value`
+    /// is enabled, the value is requested from tauri after the listener is registered, which
+    /// results in either [FieldState::Ready] or `FieldState::Error`. The returned future completes
+    /// after the value is requested.
+    pub async fn subscribe_state<P, F: Field<P>>(
+        callback: impl Fn(FieldState<F::Type>) + 'static,
+    ) -> ListenResult
+    where
+        P: Parent,
+    {
+        let callback = Rc::new(callback);
+        let revision = RevisionTracker::default();
+
+        let listen_handle = F::subscribe(ListenTarget::Any, {
+            let callback = callback.clone();
+            let revision = revision.clone();
+            move |update: Revisioned<F::Type>| {
                 log::trace!("update for {}", F::EVENT_NAME);
-                set_signal.set(value)
+                if revision.accept(update.revision) {
+                    callback(FieldState::Ready(update.value))
+                }
+            }
+        })
+        .await?;
+
+        #[cfg(feature = "initial_value")]
+        match F::get_revisioned_value().await {
+            // an emitted value could be newer than the requested one
+            Ok(initial) if revision.accept(initial.revision) => {
+                callback(FieldState::Ready(initial.value))
+            }
+            Ok(_) => {}
+            // an emitted value is preferred over the failed request
+            Err(why) if !revision.received() => callback(FieldState::Error(why)),
+            Err(why) => log::error!("{why}"),
+        }
+
+        Ok(listen_handle)
+    }
+
+    /// Registers a given event and binds a returned signal of its [FieldState]
+    ///
+    /// See [ListenHandle::subscribe_state] for the transitions of the state. Like
+    /// [ListenHandle::use_register], the [ListenHandle] is held in scope of the leptos component.
+    #[cfg(feature = "leptos")]
+    #[doc(cfg(feature = "leptos"))]
+    pub fn use_register_state<P, F: Field<P>>() -> ReadSignal<FieldState<F::Type>, LocalStorage>
+    where
+        P: Parent,
+    {
+        let (signal, set_signal) = signal_local(FieldState::Loading);
+
+        let handle = RwSignal::new_local(None);
+        leptos::task::spawn_local(async move {
+            let listen_handle = ListenHandle::subscribe_state::<P, F>(move |state| {
+                // the component could be dropped while the value is requested
+                set_signal.try_set(state);
             })
-            .await
-            .unwrap();
+            .await;
 
-            // it could be that the component doesn't live long enough, so we just try to set it
-            handle.try_set(Some(listen_handle));
+            match listen_handle {
+                Ok(listen_handle) => {
+                    handle.try_set(Some(listen_handle));
+                }
+                Err(why) => log::error!("{why}"),
+            }
         });
 
         signal
     }
 }
 
+/// The state of a [Field], which is observed without a placeholder value
+///
+/// In contrast to [Listen::listen_to] or `use_field`, observing the state doesn't require
+/// [Field::Type] to implement [Default].
+#[derive(Debug, Clone, Default)]
+pub enum FieldState<T> {
+    /// No value was received yet
+    #[default]
+    Loading,
+    /// The latest value of the field
+    Ready(T),
+    /// The value couldn't be retrieved from tauri
+    #[cfg(feature = "initial_value")]
+    #[doc(cfg(feature = "initial_value"))]
+    Error(EventError),
+}
+
+impl<T> FieldState<T> {
+    /// Returns `true` if no value was received yet
+    pub fn is_loading(&self) -> bool {
+        matches!(self, Self::Loading)
+    }
+
+    /// Returns the value, if one was received
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            Self::Ready(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Converts the state into the value, if one was received
+    pub fn into_value(self) -> Option<T> {
+        match self {
+            Self::Ready(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Tracks the newest revision received for a [Field]
+///
+/// Used to discard values, which are older than the one already applied.
+#[derive(Clone, Default)]
+pub(crate) struct RevisionTracker(Rc<Cell<Option<u64>>>);
+
+impl RevisionTracker {
+    /// Records `revision` and returns `true`, if it isn't older than the newest one
+    pub(crate) fn accept(&self, revision: u64) -> bool {
+        if self.0.get().is_some_and(|newest| revision < newest) {
+            return false;
+        }
+
+        self.0.set(Some(revision));
+        true
+    }
+
+    /// Returns `true` if any revision was accepted
+    #[cfg(feature = "initial_value")]
+    pub(crate) fn received(&self) -> bool {
+        self.0.get().is_some()
+    }
+}
+
 /// Trait that defines the available listen methods
 pub trait Listen: Sized {
     /// Registers a callback to a [Field]
     ///
-    /// Default Implementation: see [ListenHandle::register]
+    /// Default Implementation: see [Field::subscribe]
     ///
     /// ### Example
     ///
@@ -169,7 +530,64 @@ pub trait Listen: Sized {
     where
         Self: Parent,
     {
-        ListenHandle::register(F::EVENT_NAME, callback)
+        F::subscribe(ListenTarget::Any, move |update: Revisioned<F::Type>| {
+            callback(update.value)
+        })
+    }
+
+    /// Registers a callback to a [Field], which only receives events of the given target
+    ///
+    /// Default Implementation: see [Field::subscribe]
+    ///
+    /// ### Example
+    ///
+    /// ```ignore
+    /// use tauri_interop::event::{Listen, ListenTarget};
+    ///
+    /// async fn listen() {
+    ///     let _listen_handle = Test::listen_to_target::<test::FFoo>(ListenTarget::CurrentWindow, |foo| {
+    ///         /* only receives foo emitted to any or the current window */
+    ///     }).await;
+    /// }
+    /// ```
+    fn listen_to_target<F: Field<Self>>(
+        target: ListenTarget,
+        callback: impl Fn(F::Type) + 'static,
+    ) -> impl std::future::Future<Output = ListenResult>
+    where
+        Self: Parent,
+    {
+        F::subscribe(target, move |update: Revisioned<F::Type>| {
+            callback(update.value)
+        })
+    }
+
+    /// Registers a callback to the instance of an [InstanceField] with the given key
+    ///
+    /// Default Implementation: see [ListenHandle::subscribe]
+    ///
+    /// ### Example
+    ///
+    /// ```ignore
+    /// use tauri_interop::event::Listen;
+    ///
+    /// async fn listen() {
+    ///     let _listen_handle = Document::listen_to_instance::<document::FTitle>("1", |title| {
+    ///         /* only receives the title of the document with id 1 */
+    ///     }).await;
+    /// }
+    /// ```
+    fn listen_to_instance<F: InstanceField<Self>>(
+        key: &str,
+        callback: impl Fn(F::Type) + 'static,
+    ) -> impl std::future::Future<Output = ListenResult>
+    where
+        Self: Parent,
+    {
+        ListenHandle::subscribe(
+            F::instance_event_name(key),
+            move |update: Revisioned<F::Type>| callback(update.value),
+        )
     }
 
     /// Creates a signal to a [Field]
@@ -200,7 +618,104 @@ pub trait Listen: Sized {
     ) -> ReadSignal<<F as Field<Self>>::Type, LocalStorage>
     where
         Self: Parent,
+        F::Type: Default,
     {
         ListenHandle::use_register::<Self, F>(initial)
     }
+
+    /// Creates a signal to the instance of an [InstanceField] with the given key
+    ///
+    /// Default Implementation: see [ListenHandle::use_register_instance]
+    ///
+    /// ### Example
+    ///
+    /// ```ignore
+    /// use tauri_interop::event::Listen;
+    ///
+    /// #[component]
+    /// fn Title(id: u32) -> impl IntoView {
+    ///     let title = Document::use_field_instance::<document::FTitle>(id.to_string(), None);
+    ///
+    ///     view! { <h1>{title}</h1> }
+    /// }
+    /// ```
+    #[cfg(feature = "leptos")]
+    #[doc(cfg(feature = "leptos"))]
+    fn use_field_instance<F: InstanceField<Self>>(
+        key: impl Into<String>,
+        initial: Option<F::Type>,
+    ) -> ReadSignal<<F as Field<Self>>::Type, LocalStorage>
+    where
+        Self: Parent,
+        F::Type: Default,
+    {
+        ListenHandle::use_register_instance::<Self, F>(key, initial)
+    }
+
+    /// Creates a signal and a setter to a writable [Field]
+    ///
+    /// Default Implementation: see [ListenHandle::use_register_writable]
+    ///
+    /// ### Example
+    ///
+    /// ```ignore
+    /// use tauri_interop::Event;
+    ///
+    /// #[derive(Default, Event)]
+    /// pub struct Test {
+    ///     #[event(writable)]
+    ///     foo: String,
+    /// }
+    ///
+    /// #[component]
+    /// fn Foo() -> impl IntoView {
+    ///     use tauri_interop::event::listen::Listen;
+    ///
+    ///     let (foo, set_foo) = Test::use_field_writable::<test::FFoo>(None);
+    ///
+    ///     view! { <input prop:value=foo on:input=move |ev| set_foo.set(event_target_value(&ev)) /> }
+    /// }
+    /// ```
+    #[cfg(all(feature = "leptos", feature = "initial_value"))]
+    #[doc(cfg(all(feature = "leptos", feature = "initial_value")))]
+    fn use_field_writable<F: Field<Self>>(
+        initial: Option<F::Type>,
+    ) -> (
+        ReadSignal<F::Type, LocalStorage>,
+        SignalSetter<F::Type, LocalStorage>,
+    )
+    where
+        Self: Parent,
+        F::Type: Default,
+    {
+        ListenHandle::use_register_writable::<Self, F>(initial)
+    }
+
+    /// Creates a signal of the [FieldState] of a [Field]
+    ///
+    /// Default Implementation: see [ListenHandle::use_register_state]
+    ///
+    /// ### Example
+    ///
+    /// ```ignore
+    /// use tauri_interop::event::{FieldState, Listen};
+    ///
+    /// #[component]
+    /// fn Foo() -> impl IntoView {
+    ///     let foo = Test::use_field_state::<test::FFoo>();
+    ///
+    ///     move || match foo.get() {
+    ///         FieldState::Ready(foo) => foo.into_any(),
+    ///         _ => "loading...".into_any(),
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "leptos")]
+    #[doc(cfg(feature = "leptos"))]
+    fn use_field_state<F: Field<Self>>() -> ReadSignal<FieldState<F::Type>, LocalStorage>
+    where
+        Self: Parent,
+    {
+        ListenHandle::use_register_state::<Self, F>()
+    }
 }
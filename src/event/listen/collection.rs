@@ -0,0 +1,174 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[cfg(feature = "initial_value")]
+use futures::future::LocalBoxFuture;
+
+use super::{Detach, ListenHandle, ListenResult, ListenTarget};
+#[cfg(feature = "initial_value")]
+use crate::event::EventError;
+use crate::event::{Collection, Revisioned, RevisionedDelta};
+
+impl ListenHandle {
+    /// Subscribes a callback to a field marked with `#[event(collection)]`
+    ///
+    /// The value is replaced by the events of the field itself and patched by its deltas (see
+    /// `CollectionField`). A delta, whose previous change wasn't received, discards the value,
+    /// as a change was missed. Then the value is requested again with `get_value`, when feature
+    /// `initial_value` is enabled, or otherwise awaited from the next event of the field. The
+    /// deltas received while the value is requested are buffered and applied on top of it. All
+    /// listeners are detached, when the returned handle is dropped.
+    pub async fn subscribe_collection<C>(
+        event: &'static str,
+        target: ListenTarget,
+        #[cfg(feature = "initial_value")] get_value: fn() -> LocalBoxFuture<
+            'static,
+            Result<Revisioned<C>, EventError>,
+        >,
+        callback: impl Fn(Revisioned<C>) + 'static,
+    ) -> ListenResult
+    where
+        C: Collection,
+    {
+        let callback = Rc::new(callback);
+        let value = Rc::new(RefCell::new(None::<Revisioned<C>>));
+
+        // replaces the value, unless a newer one was already received
+        let replace = Rc::new({
+            let callback = callback.clone();
+            let value = value.clone();
+            move |update: Revisioned<C>| {
+                let outdated = value
+                    .borrow()
+                    .as_ref()
+                    .is_some_and(|current| update.revision < current.revision);
+                if !outdated {
+                    value.replace(Some(update.clone()));
+                    callback(update)
+                }
+            }
+        });
+
+        // requests the value again, while the received deltas are buffered
+        #[cfg(feature = "initial_value")]
+        let buffered = Rc::new(RefCell::new(None::<Vec<RevisionedDelta<C::Key, C::Item>>>));
+        #[cfg(feature = "initial_value")]
+        let resync = {
+            let callback = callback.clone();
+            let replace = replace.clone();
+            let value = value.clone();
+            let buffered = buffered.clone();
+            move || {
+                if buffered.borrow().is_some() {
+                    return;
+                }
+
+                buffered.replace(Some(Vec::new()));
+                let (callback, value, buffered) =
+                    (callback.clone(), value.clone(), buffered.clone());
+                let replace = replace.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    loop {
+                        let requested = get_value().await;
+                        let mut deltas = buffered.take().unwrap_or_default();
+                        match requested {
+                            Ok(requested) => replace(requested),
+                            Err(why) => {
+                                log::error!("{why}");
+                                return;
+                            }
+                        }
+
+                        // the deltas, which are newer than the requested value, are applied on top
+                        deltas.sort_by_key(|delta| delta.revision);
+                        let missed = deltas.into_iter().any(|delta| match patch(&value, delta) {
+                            Patch::Contained => false,
+                            Patch::Patched(patched) => {
+                                callback(patched);
+                                false
+                            }
+                            Patch::Missed => true,
+                        });
+                        if !missed {
+                            return;
+                        }
+
+                        log::trace!(
+                            "missed a change of {event} while resyncing, requesting it again"
+                        );
+                        buffered.replace(Some(Vec::new()));
+                    }
+                });
+            }
+        };
+
+        let listen_handle = ListenHandle::subscribe_with_target(event, target.clone(), {
+            let replace = replace.clone();
+            move |update: Revisioned<C>| replace(update)
+        })
+        .await?;
+
+        let delta_event = format!("{event}::delta");
+        let delta_handle = ListenHandle::subscribe_with_target(
+            delta_event,
+            target,
+            move |update: RevisionedDelta<C::Key, C::Item>| {
+                #[cfg(feature = "initial_value")]
+                if let Some(deltas) = buffered.borrow_mut().as_mut() {
+                    deltas.push(update);
+                    return;
+                }
+
+                match patch(&value, update) {
+                    Patch::Contained => {}
+                    Patch::Patched(patched) => callback(patched),
+                    Patch::Missed => {
+                        log::trace!("missed a change of {event}, discarded its value");
+                        #[cfg(feature = "initial_value")]
+                        resync();
+                    }
+                }
+            },
+        )
+        .await?;
+
+        Ok(ListenHandle {
+            event: event.into(),
+            closure: None,
+            detach: Detach::Subscriber(Box::new(vec![listen_handle, delta_handle])),
+        })
+    }
+}
+
+enum Patch<C> {
+    /// The delta is already contained in the value
+    Contained,
+    /// The value with the applied delta
+    Patched(Revisioned<C>),
+    /// The previous change of the delta wasn't received, so the value was discarded
+    Missed,
+}
+
+/// Applies the delta to the value, if its previous change was received
+fn patch<C: Collection>(
+    value: &RefCell<Option<Revisioned<C>>>,
+    update: RevisionedDelta<C::Key, C::Item>,
+) -> Patch<C> {
+    let mut current = value.borrow_mut();
+    let patched = match current.as_mut() {
+        Some(current) if update.revision <= current.revision => return Patch::Contained,
+        Some(current) if update.previous <= current.revision => {
+            current.revision = update.revision;
+            current.value.apply(update.delta).then(|| current.clone())
+        }
+        _ => None,
+    };
+
+    match patched {
+        Some(patched) => Patch::Patched(patched),
+        None => {
+            *current = None;
+            Patch::Missed
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use futures::future::{FutureExt, LocalBoxFuture};
+use serde::de::DeserializeOwned;
+
+use super::{Detach, ListenHandle, ListenResult, ListenTarget, RevisionTracker};
+#[cfg(feature = "initial_value")]
+use crate::event::EventError;
+use crate::event::Revisioned;
+
+type Patch<T> = Box<dyn FnOnce(&mut T)>;
+type Patches<T> = Rc<dyn Fn(u64, Patch<T>)>;
+type Subscribe<T> =
+    Box<dyn FnOnce(ListenTarget, Patches<T>) -> LocalBoxFuture<'static, ListenResult>>;
+
+/// A field nested into the field of another struct with `#[event(nested)]`
+///
+/// The events of a nested field only contain its own value, which is applied to the last
+/// received value of the parent field by [ListenHandle::subscribe_nested].
+pub struct NestedField<T> {
+    subscribe: Subscribe<T>,
+}
+
+impl<T: 'static> NestedField<T> {
+    /// Creates a nested field, whose values of `event` are applied with `apply`
+    pub fn new<V>(event: &'static str, apply: fn(&mut T, V)) -> Self
+    where
+        V: DeserializeOwned + Clone + 'static,
+    {
+        let subscribe = move |target, patches: Patches<T>| {
+            ListenHandle::subscribe_with_target(event, target, move |update: Revisioned<V>| {
+                patches(
+                    update.revision,
+                    Box::new(move |parent| apply(parent, update.value)),
+                )
+            })
+            .boxed_local()
+        };
+
+        Self {
+            subscribe: Box::new(subscribe),
+        }
+    }
+}
+
+impl ListenHandle {
+    /// Subscribes a callback to a field, whose value is composed of nested fields
+    ///
+    /// The value is replaced by the events of the field itself and patched by the events of the
+    /// `nested` fields. A nested event received before any value of the field is discarded,
+    /// unless feature `initial_value` is enabled, in which case the value is requested with
+    /// `get_value`. All listeners are detached, when the returned handle is dropped.
+    pub async fn subscribe_nested<T>(
+        event: &'static str,
+        target: ListenTarget,
+        nested: Vec<NestedField<T>>,
+        #[cfg(feature = "initial_value")] get_value: fn() -> LocalBoxFuture<
+            'static,
+            Result<Revisioned<T>, EventError>,
+        >,
+        callback: impl Fn(Revisioned<T>) + 'static,
+    ) -> ListenResult
+    where
+        T: DeserializeOwned + Clone + 'static,
+    {
+        let callback = Rc::new(callback);
+        let value = Rc::new(RefCell::new(None::<T>));
+        let revision = RevisionTracker::default();
+
+        let listen_handle = ListenHandle::subscribe_with_target(event, target.clone(), {
+            let callback = callback.clone();
+            let value = value.clone();
+            let revision = revision.clone();
+            move |update: Revisioned<T>| {
+                if revision.accept(update.revision) {
+                    value.replace(Some(update.value.clone()));
+                    callback(update)
+                }
+            }
+        })
+        .await?;
+
+        let patches: Patches<T> = Rc::new(move |update_revision, patch| {
+            // a newer value of the field already contains the nested value
+            if !revision.accept(update_revision) {
+                return;
+            }
+
+            let patched = value.borrow_mut().as_mut().map(|value| {
+                patch(value);
+                value.clone()
+            });
+            match patched {
+                Some(patched) => callback(Revisioned {
+                    revision: update_revision,
+                    value: patched,
+                }),
+                // the requested value contains the nested value, as it was emitted beforehand
+                #[cfg(feature = "initial_value")]
+                None => {
+                    let callback = callback.clone();
+                    let value = value.clone();
+                    let revision = revision.clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        match get_value().await {
+                            Ok(initial) if revision.accept(initial.revision) => {
+                                value.replace(Some(initial.value.clone()));
+                                callback(initial)
+                            }
+                            Ok(_) => {}
+                            Err(why) => log::error!("{why}"),
+                        }
+                    });
+                }
+                #[cfg(not(feature = "initial_value"))]
+                None => log::trace!("discarded nested update for {event}, no value received yet"),
+            }
+        });
+
+        let mut listen_handles = vec![listen_handle];
+        for field in nested {
+            listen_handles.push((field.subscribe)(target.clone(), patches.clone()).await?);
+        }
+
+        Ok(ListenHandle {
+            event: event.into(),
+            closure: None,
+            detach: Detach::Subscriber(Box::new(listen_handles)),
+        })
+    }
+}
@@ -0,0 +1,274 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::rc::{Rc, Weak};
+use std::task::{Context, Poll};
+
+use futures::channel::mpsc;
+use futures::{Stream, StreamExt};
+
+use super::{ListenError, ListenHandle, ListenTarget, Parent, RevisionTracker};
+use crate::event::{Field, Revisioned};
+
+type Callback<T> = Rc<dyn Fn(&T)>;
+
+struct Inner<T> {
+    /// The value is shared with running callbacks, so that they can mutate the observable
+    value: Rc<T>,
+    version: u64,
+    next_id: u64,
+    subscribers: BTreeMap<u64, Callback<T>>,
+}
+
+/// A value which notifies its subscribers about every change
+///
+/// The observable isn't bound to any ui framework, subscribers can be registered as
+/// callback ([Observable::subscribe]) or consumed as [Stream] ([Observable::stream]).
+/// Cloning the observable shares the value and the subscribers.
+///
+/// ### Example
+///
+/// ```ignore
+/// use tauri_interop::event::Observable;
+///
+/// let observable = Observable::new(0);
+/// let subscription = observable.subscribe(|value| log::info!("changed to {value}"));
+///
+/// observable.set(1);
+/// drop(subscription);
+/// ```
+pub struct Observable<T> {
+    inner: Rc<RefCell<Inner<T>>>,
+}
+
+impl<T> Clone for Observable<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: Default + 'static> Default for Observable<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: 'static> Observable<T> {
+    /// Creates an observable with the given value
+    pub fn new(value: T) -> Self {
+        Self {
+            inner: Rc::new(RefCell::new(Inner {
+                value: Rc::new(value),
+                version: 0,
+                next_id: 0,
+                subscribers: BTreeMap::new(),
+            })),
+        }
+    }
+
+    /// Returns a clone of the current value
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        T::clone(&self.inner.borrow().value)
+    }
+
+    /// Calls `f` with a reference to the current value
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        let value = self.inner.borrow().value.clone();
+        f(&value)
+    }
+
+    /// Replaces the value and notifies all subscribers
+    pub fn set(&self, value: T) {
+        let value = Rc::new(value);
+        let subscribers = {
+            let mut inner = self.inner.borrow_mut();
+            inner.value = value.clone();
+            inner.version += 1;
+            inner.subscribers.values().cloned().collect::<Vec<_>>()
+        };
+
+        // the subscribers receive a snapshot without a borrow of the observable, so that they
+        // can set the observable, subscribe or drop their subscription
+        for subscriber in subscribers {
+            subscriber(&value)
+        }
+    }
+
+    /// The amount of changes since the observable was created
+    pub fn version(&self) -> u64 {
+        self.inner.borrow().version
+    }
+
+    /// Registers a callback, which is called with the new value after every change
+    ///
+    /// The callback is removed when the returned [Subscription] is dropped.
+    pub fn subscribe(&self, callback: impl Fn(&T) + 'static) -> Subscription {
+        let mut inner = self.inner.borrow_mut();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.subscribers.insert(id, Rc::new(callback));
+
+        let observable = Rc::downgrade(&self.inner);
+        Subscription {
+            unsubscribe: Some(Box::new(move || {
+                if let Some(observable) = Weak::upgrade(&observable) {
+                    observable.borrow_mut().subscribers.remove(&id);
+                }
+            })),
+        }
+    }
+
+    /// Returns a [Stream], which yields the current value followed by every change
+    pub fn stream(&self) -> ObservableStream<T>
+    where
+        T: Clone,
+    {
+        let (sender, receiver) = mpsc::unbounded();
+        let _ = sender.unbounded_send(self.get());
+        let subscription = self.subscribe(move |value| {
+            let _ = sender.unbounded_send(value.clone());
+        });
+
+        ObservableStream {
+            receiver,
+            _subscription: subscription,
+        }
+    }
+}
+
+/// Handle of a subscriber registered with [Observable::subscribe]
+///
+/// The subscriber is removed, when the subscription is dropped.
+pub struct Subscription {
+    unsubscribe: Option<Box<dyn FnOnce()>>,
+}
+
+impl Subscription {
+    /// Removes the subscriber
+    pub fn unsubscribe(mut self) {
+        if let Some(unsubscribe) = self.unsubscribe.take() {
+            unsubscribe()
+        }
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Some(unsubscribe) = self.unsubscribe.take() {
+            unsubscribe()
+        }
+    }
+}
+
+/// [Stream] of the values of an [Observable], returned by [Observable::stream]
+pub struct ObservableStream<T> {
+    receiver: mpsc::UnboundedReceiver<T>,
+    _subscription: Subscription,
+}
+
+impl<T> Stream for ObservableStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
+
+/// Holds the latest value of a [Field] as [Observable]
+///
+/// The store listens to the event of the field until it is dropped. Clones of the
+/// [Observable] keep the last received value, but aren't updated anymore afterward.
+///
+/// ### Example
+///
+/// ```ignore
+/// use tauri_interop::event::FieldStore;
+///
+/// async fn observe() {
+///     let store = FieldStore::<TestState, test_mod::FFoo>::new(None)
+///         .await
+///         .expect("listening to foo");
+///
+///     let subscription = store.subscribe(|foo| log::info!("foo: {foo}"));
+/// }
+/// ```
+pub struct FieldStore<P, F: Field<P>>
+where
+    P: Parent,
+{
+    observable: Observable<F::Type>,
+    _listen_handle: ListenHandle,
+    _marker: PhantomData<fn() -> P>,
+}
+
+impl<P, F> FieldStore<P, F>
+where
+    P: Parent,
+    F: Field<P>,
+    F::Type: Default,
+{
+    /// Registers the listener of the field and creates the store
+    ///
+    /// Providing [None] will unwrap into the default value. When feature `initial_value`
+    /// is enabled [None] will try to get the value from tauri.
+    pub async fn new(initial_value: Option<F::Type>) -> Result<Self, ListenError> {
+        #[cfg(feature = "initial_value")]
+        let acquire_initial_value = initial_value.is_none();
+        let observable = Observable::new(initial_value.unwrap_or_default());
+
+        let revision = RevisionTracker::default();
+
+        let listen_handle = F::subscribe(ListenTarget::Any, {
+            let observable = observable.clone();
+            let revision = revision.clone();
+            move |update: Revisioned<F::Type>| {
+                log::trace!("update for {}", F::EVENT_NAME);
+                if revision.accept(update.revision) {
+                    observable.set(update.value)
+                }
+            }
+        })
+        .await?;
+
+        // the listener is registered beforehand, so that no change is missed in the meantime
+        #[cfg(feature = "initial_value")]
+        if acquire_initial_value {
+            match F::get_revisioned_value().await {
+                // an emitted value could be newer than the requested one
+                Ok(initial) if revision.accept(initial.revision) => observable.set(initial.value),
+                Ok(_) => {}
+                Err(why) => log::error!("{why}"),
+            }
+        }
+
+        Ok(Self {
+            observable,
+            _listen_handle: listen_handle,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns the observable of the field
+    pub fn observable(&self) -> Observable<F::Type> {
+        self.observable.clone()
+    }
+}
+
+impl<P, F> Deref for FieldStore<P, F>
+where
+    P: Parent,
+    F: Field<P>,
+{
+    type Target = Observable<F::Type>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.observable
+    }
+}
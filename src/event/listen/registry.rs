@@ -0,0 +1,435 @@
+use std::any::{Any, TypeId};
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::future::Future;
+use std::rc::{Rc, Weak};
+
+use futures::channel::oneshot;
+use futures::future::{LocalBoxFuture, Shared};
+use futures::FutureExt;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use wasm_bindgen::JsValue;
+
+use super::{Detach, ListenError, ListenHandle, ListenResult, ListenTarget};
+use crate::event::BATCH_EVENT;
+
+type Key = (String, ListenTarget, TypeId);
+type Registration = Shared<LocalBoxFuture<'static, Result<(), ListenError>>>;
+
+/// The shared listener of an event, which dispatches each payload to all subscribers
+struct Channel<T> {
+    key: Key,
+    next_id: Cell<u64>,
+    subscribers: RefCell<BTreeMap<u64, Rc<dyn Fn(&T)>>>,
+    listen_handle: RefCell<Option<ListenHandle>>,
+    /// Receives the events of the channel, which were emitted by a `Transaction`
+    _batched: Option<Batched>,
+    /// The revisions received in a batch, whose field events weren't received yet
+    batched_revisions: RefCell<BTreeSet<u64>>,
+}
+
+impl<T> Channel<T> {
+    fn dispatch(&self, value: &T) {
+        // subscribers are collected beforehand, so that a callback can (un)subscribe itself
+        let subscribers = self
+            .subscribers
+            .borrow()
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        for subscriber in subscribers {
+            subscriber(value)
+        }
+    }
+
+    /// Dispatches a value received in a batch, see `Transaction`
+    fn dispatch_batched(&self, value: &T, revision: Option<u64>) {
+        if let Some(revision) = revision {
+            self.batched_revisions.borrow_mut().insert(revision);
+        }
+        self.dispatch(value)
+    }
+
+    /// Dispatches a value of the event, unless it was already received in a batch
+    fn dispatch_emitted(&self, value: &T, revision: Option<u64>) {
+        if let Some(revision) = revision {
+            let mut batched_revisions = self.batched_revisions.borrow_mut();
+            let received = batched_revisions.remove(&revision);
+            // the field events follow their batch, so older revisions won't be received anymore
+            batched_revisions.retain(|batched| *batched > revision);
+            if received {
+                return;
+            }
+        }
+        self.dispatch(value)
+    }
+}
+
+impl<T> Drop for Channel<T> {
+    fn drop(&mut self) {
+        let _ = CHANNELS.try_with(|channels| {
+            if let Ok(mut channels) = channels.try_borrow_mut() {
+                // the entry could be replaced by a new channel in the meantime
+                if channels
+                    .get(&self.key)
+                    .is_some_and(|entry| entry.channel.strong_count() == 0)
+                {
+                    channels.remove(&self.key);
+                }
+            }
+        });
+    }
+}
+
+/// Removes the subscriber from its channel when dropped
+struct Subscriber<T> {
+    channel: Rc<Channel<T>>,
+    id: u64,
+}
+
+impl<T> Drop for Subscriber<T> {
+    fn drop(&mut self) {
+        self.channel.subscribers.borrow_mut().remove(&self.id);
+    }
+}
+
+struct Entry {
+    channel: Weak<dyn Any>,
+    registration: Registration,
+}
+
+/// The listener of the batches of a target, which is shared by the channels of the target
+///
+/// The listener is registered with the first channel and detached with the last one.
+struct BatchListener {
+    target: ListenTarget,
+    next_id: Cell<u64>,
+    /// The channels of the target by their event
+    receivers: RefCell<BTreeMap<u64, (String, Rc<dyn Fn(JsValue)>)>>,
+    listen_handle: RefCell<Option<ListenHandle>>,
+}
+
+impl BatchListener {
+    /// Dispatches the events of a batch in order to the channels of their event
+    fn dispatch(&self, batch: Batch) {
+        for BatchEvent { event, payload } in batch.events {
+            // the receivers are collected beforehand, so that a callback can (un)subscribe
+            let receivers = self
+                .receivers
+                .borrow()
+                .values()
+                .filter(|(receiver_event, _)| *receiver_event == event)
+                .map(|(_, receiver)| receiver.clone())
+                .collect::<Vec<_>>();
+            for receiver in receivers {
+                receiver(payload.clone())
+            }
+        }
+    }
+}
+
+impl Drop for BatchListener {
+    fn drop(&mut self) {
+        let _ = BATCH_LISTENERS.try_with(|listeners| {
+            if let Ok(mut listeners) = listeners.try_borrow_mut() {
+                // the entry could be replaced by a new listener in the meantime
+                if listeners
+                    .get(&self.target)
+                    .is_some_and(|(listener, _)| listener.strong_count() == 0)
+                {
+                    listeners.remove(&self.target);
+                }
+            }
+        });
+    }
+}
+
+/// Removes a channel from the receivers of its batch listener when dropped
+struct Batched {
+    listener: Rc<BatchListener>,
+    id: u64,
+}
+
+impl Drop for Batched {
+    fn drop(&mut self) {
+        self.listener.receivers.borrow_mut().remove(&self.id);
+    }
+}
+
+/// The field events of a `Transaction`, which are dispatched together
+#[derive(Deserialize)]
+struct Batch {
+    events: Vec<BatchEvent>,
+}
+
+#[derive(Deserialize)]
+struct BatchEvent {
+    event: String,
+    /// The payload is deserialized by each channel of the event
+    #[serde(with = "serde_wasm_bindgen::preserve")]
+    payload: JsValue,
+}
+
+/// The payload of an event, which is deserialized by the channel
+#[derive(Deserialize)]
+#[serde(transparent)]
+struct RawPayload(#[serde(with = "serde_wasm_bindgen::preserve")] JsValue);
+
+/// The revision of a field event, see `Revisioned`
+#[derive(Deserialize)]
+struct Revision {
+    revision: u64,
+}
+
+thread_local! {
+    static CHANNELS: RefCell<HashMap<Key, Entry>> = RefCell::default();
+    static BATCH_LISTENERS: RefCell<HashMap<ListenTarget, (Weak<BatchListener>, Registration)>> =
+        RefCell::default();
+}
+
+impl ListenHandle {
+    /// Subscribes a callback to a given event and returns a [ListenResult]
+    ///
+    /// In contrast to [ListenHandle::register] all subscribers of an event share a single
+    /// listener in tauri, the payload is deserialized once and cloned for each subscriber.
+    /// The shared listener is detached, when the last subscriber is dropped. The returned
+    /// handle holds no `closure`, to keep the subscriber see [ListenHandle::forget].
+    pub async fn subscribe<T>(
+        event: impl Into<Cow<'static, str>>,
+        callback: impl Fn(T) + 'static,
+    ) -> ListenResult
+    where
+        T: DeserializeOwned + Clone + 'static,
+    {
+        Self::subscribe_with_target(event, ListenTarget::Any, callback).await
+    }
+
+    /// Subscribes a callback to a given event of the given target and returns a [ListenResult]
+    ///
+    /// The subscribers share a listener per event and target, see [ListenHandle::subscribe].
+    pub async fn subscribe_with_target<T>(
+        event: impl Into<Cow<'static, str>>,
+        target: ListenTarget,
+        callback: impl Fn(T) + 'static,
+    ) -> ListenResult
+    where
+        T: DeserializeOwned + Clone + 'static,
+    {
+        let event = event.into();
+        let (channel, registration) = CHANNELS.with_borrow_mut(|channels| {
+            let key = (event.to_string(), target, TypeId::of::<T>());
+            let existing = channels.get(&key).and_then(|entry| {
+                let channel = entry.channel.upgrade()?.downcast::<Channel<T>>().ok()?;
+                Some((channel, entry.registration.clone()))
+            });
+            if let Some(existing) = existing {
+                return existing;
+            }
+
+            // the listener of the batches itself doesn't receive batched events
+            let (batch_listener, batch_registration) = (event != BATCH_EVENT)
+                .then(|| batch_listener(&key.1))
+                .unzip();
+            let channel = Rc::new_cyclic(|channel: &Weak<Channel<T>>| Channel {
+                key: key.clone(),
+                next_id: Cell::new(0),
+                subscribers: RefCell::default(),
+                listen_handle: RefCell::new(None),
+                _batched: batch_listener.map(|listener| batched(listener, &event, channel.clone())),
+                batched_revisions: RefCell::default(),
+            });
+            let registration = register(key.clone(), Rc::downgrade(&channel), batch_registration);
+            let erased: Rc<dyn Any> = channel.clone();
+            channels.insert(
+                key,
+                Entry {
+                    channel: Rc::downgrade(&erased),
+                    registration: registration.clone(),
+                },
+            );
+
+            (channel, registration)
+        });
+
+        // the subscriber is added beforehand, so that it receives every event after registering
+        let id = channel.next_id.replace(channel.next_id.get() + 1);
+        channel
+            .subscribers
+            .borrow_mut()
+            .insert(id, Rc::new(move |value: &T| callback(value.clone())));
+        let subscriber = Subscriber { channel, id };
+
+        registration.await?;
+
+        Ok(ListenHandle {
+            closure: None,
+            event,
+            detach: Detach::Subscriber(Box::new(subscriber)),
+        })
+    }
+}
+
+/// Adds a channel to the receivers of the batches of its target
+fn batched<T>(listener: Rc<BatchListener>, event: &str, channel: Weak<Channel<T>>) -> Batched
+where
+    T: DeserializeOwned + 'static,
+{
+    let id = listener.next_id.replace(listener.next_id.get() + 1);
+    let receiver = move |payload| {
+        let Some(channel) = channel.upgrade() else {
+            return;
+        };
+
+        let revision = revision(&payload);
+        match serde_wasm_bindgen::from_value(payload) {
+            Ok(value) => channel.dispatch_batched(&value, revision),
+            Err(why) => log::error!("batched payload didn't deserialize correctly: {why}"),
+        }
+    };
+    listener
+        .receivers
+        .borrow_mut()
+        .insert(id, (event.into(), Rc::new(receiver)));
+
+    Batched { listener, id }
+}
+
+/// Registers the shared listener of a channel
+///
+/// Channels of field events additionally wait for the listener of the batches of their target
+/// (see `Emit::transaction`). The registration is spawned, so that it completes even if all
+/// subscribers are dropped while waiting. In that case the handle is dropped right away and
+/// the listener detached.
+fn register<T>(key: Key, channel: Weak<Channel<T>>, batch: Option<Registration>) -> Registration
+where
+    T: DeserializeOwned + 'static,
+{
+    spawn_registration(async move {
+        let (event, target, _) = key.clone();
+        let dispatch = channel.clone();
+        let result = ListenHandle::register_with_target(event, target, move |payload| {
+            let Some(channel) = dispatch.upgrade() else {
+                return;
+            };
+
+            let RawPayload(payload) = payload;
+            let revision = revision(&payload);
+            match serde_wasm_bindgen::from_value(payload) {
+                Ok(value) => channel.dispatch_emitted(&value, revision),
+                Err(why) => log::error!("payload didn't deserialize correctly: {why}"),
+            }
+        })
+        .await;
+
+        let result = match result {
+            Ok(listen_handle) => {
+                if let Some(channel) = channel.upgrade() {
+                    channel.listen_handle.replace(Some(listen_handle));
+                }
+                Ok(())
+            }
+            Err(why) => Err(why),
+        };
+        let result = match (result, batch) {
+            (Ok(()), Some(batch)) => batch.await,
+            (result, _) => result,
+        };
+
+        // a failed registration shouldn't be reused by later subscribers
+        if result.is_err() {
+            CHANNELS.with_borrow_mut(|channels| channels.remove(&key));
+        }
+        result
+    })
+}
+
+/// Returns the listener of the batches of a target, which is registered by the first channel
+fn batch_listener(target: &ListenTarget) -> (Rc<BatchListener>, Registration) {
+    BATCH_LISTENERS.with_borrow_mut(|listeners| {
+        let existing = listeners
+            .get(target)
+            .and_then(|(listener, registration)| Some((listener.upgrade()?, registration.clone())));
+        if let Some(existing) = existing {
+            return existing;
+        }
+
+        let listener = Rc::new(BatchListener {
+            target: target.clone(),
+            next_id: Cell::new(0),
+            receivers: RefCell::default(),
+            listen_handle: RefCell::new(None),
+        });
+        let registration = register_batch(target.clone(), Rc::downgrade(&listener));
+        listeners.insert(
+            target.clone(),
+            (Rc::downgrade(&listener), registration.clone()),
+        );
+
+        (listener, registration)
+    })
+}
+
+/// Registers the listener of the batches of a target, see [register]
+fn register_batch(target: ListenTarget, listener: Weak<BatchListener>) -> Registration {
+    spawn_registration(async move {
+        let dispatch = listener.clone();
+        let result =
+            ListenHandle::register_with_target(BATCH_EVENT, target.clone(), move |batch| {
+                if let Some(listener) = dispatch.upgrade() {
+                    listener.dispatch(batch)
+                }
+            })
+            .await;
+
+        match result {
+            Ok(listen_handle) => {
+                if let Some(listener) = listener.upgrade() {
+                    listener.listen_handle.replace(Some(listen_handle));
+                }
+                Ok(())
+            }
+            Err(why) => {
+                // a failed registration is retried by the next channel
+                BATCH_LISTENERS.with_borrow_mut(|listeners| {
+                    if listeners
+                        .get(&target)
+                        .is_some_and(|(entry, _)| entry.ptr_eq(&listener))
+                    {
+                        listeners.remove(&target);
+                    }
+                });
+                Err(why)
+            }
+        }
+    })
+}
+
+/// Spawns a registration, which can be awaited by multiple subscribers
+fn spawn_registration(
+    registration: impl Future<Output = Result<(), ListenError>> + 'static,
+) -> Registration {
+    let (sender, receiver) = oneshot::channel();
+    wasm_bindgen_futures::spawn_local(async move {
+        let _ = sender.send(registration.await);
+    });
+
+    receiver
+        .map(|result| {
+            result.unwrap_or_else(|_| {
+                Err(ListenError::PromiseFailed(JsValue::from_str(
+                    "registration of the shared listener was dropped",
+                )))
+            })
+        })
+        .boxed_local()
+        .shared()
+}
+
+/// Returns the revision of a payload, if it is the payload of a field event
+fn revision(payload: &JsValue) -> Option<u64> {
+    serde_wasm_bindgen::from_value::<Revision>(payload.clone())
+        .ok()
+        .map(|Revision { revision }| revision)
+}
@@ -26,6 +26,9 @@
 #![feature(doc_cfg)]
 #![warn(missing_docs)]
 
+#[cfg(any(target_family = "wasm", doc))]
+#[doc(cfg(target_family = "wasm"))]
+pub use command::batch::batch;
 #[cfg(any(target_family = "wasm", doc))]
 #[doc(cfg(target_family = "wasm"))]
 pub use tauri_interop_macro::binding;
@@ -43,6 +46,15 @@ pub mod command;
 #[cfg(feature = "event")]
 #[doc(cfg(feature = "event"))]
 pub mod event;
+/// hooks to use events and commands in [yew](https://yew.rs) components
+#[cfg(all(any(target_family = "wasm", doc), feature = "yew"))]
+#[doc(cfg(all(target_family = "wasm", feature = "yew")))]
+pub mod yew;
+
+/// hooks to use events and commands in [dioxus](https://dioxuslabs.com) components
+#[cfg(all(any(target_family = "wasm", doc), feature = "dioxus"))]
+#[doc(cfg(all(target_family = "wasm", feature = "dioxus")))]
+pub mod dioxus;
 
 #[doc(hidden)]
 pub mod export {
@@ -52,6 +64,14 @@ pub mod export {
     #[cfg(target_family = "wasm")]
     pub use serde_wasm_bindgen;
 
+    #[cfg(all(target_family = "wasm", feature = "leptos"))]
+    pub use leptos;
+
+    #[cfg(all(target_family = "wasm", feature = "dioxus"))]
+    pub use dioxus;
+
+    #[cfg(not(target_family = "wasm"))]
+    pub use serde_json;
     #[cfg(not(target_family = "wasm"))]
     pub use tauri;
 }
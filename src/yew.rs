@@ -0,0 +1,318 @@
+use std::cell::RefCell;
+use std::future::Future;
+use std::rc::Rc;
+
+use yew::prelude::*;
+
+#[cfg(feature = "event")]
+use crate::event::{
+    Field, FieldState, ListenHandle, ListenTarget, Parent, RevisionTracker, Revisioned,
+};
+
+/// Registers a listener to a [Field] and returns its current value
+///
+/// Providing [None] will unwrap into the default value. When feature `initial_value`
+/// is enabled [None] will try to get the value from tauri.
+///
+/// The listener is detached, when the component is unmounted.
+///
+/// ### Example
+///
+/// ```ignore
+/// #[function_component]
+/// fn Foo() -> Html {
+///     let foo = tauri_interop::yew::use_field::<TestState, test_mod::FFoo>(None);
+///
+///     html! { <h1>{foo}</h1> }
+/// }
+/// ```
+#[cfg(feature = "event")]
+#[doc(cfg(feature = "event"))]
+#[hook]
+pub fn use_field<P, F>(initial_value: Option<F::Type>) -> F::Type
+where
+    P: Parent + 'static,
+    F: Field<P> + 'static,
+    F::Type: Default,
+{
+    #[cfg(feature = "initial_value")]
+    let acquire_initial_value = initial_value.is_none();
+    let value = use_state(move || initial_value.unwrap_or_default());
+
+    {
+        let value = value.clone();
+        use_effect_with((), move |_| {
+            // holds the handle until the component is unmounted
+            let handle = Rc::new(RefCell::new(None));
+            let mounted = Rc::new(RefCell::new(true));
+
+            {
+                let handle = handle.clone();
+                let mounted = mounted.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    let revision = RevisionTracker::default();
+
+                    // the listener is registered first, so that no update is missed while acquiring the value
+                    let listen_handle = F::subscribe(ListenTarget::Any, {
+                        let value = value.clone();
+                        let revision = revision.clone();
+                        move |update: Revisioned<F::Type>| {
+                            log::trace!("update for {}", F::EVENT_NAME);
+                            if revision.accept(update.revision) {
+                                value.set(update.value)
+                            }
+                        }
+                    })
+                    .await;
+
+                    match listen_handle {
+                        Ok(listen_handle) if *mounted.borrow() => {
+                            handle.replace(Some(listen_handle));
+                        }
+                        // the component is unmounted already, in which case the handle is dropped
+                        Ok(_) => return,
+                        Err(why) => log::error!("{why}"),
+                    }
+
+                    #[cfg(feature = "initial_value")]
+                    if acquire_initial_value {
+                        match F::get_revisioned_value().await {
+                            // an emitted value could be newer than the requested one
+                            Ok(initial) if revision.accept(initial.revision) => {
+                                value.set(initial.value)
+                            }
+                            Ok(_) => {}
+                            Err(why) => log::error!("{why}"),
+                        }
+                    }
+                });
+            }
+
+            move || {
+                mounted.replace(false);
+                handle.take();
+            }
+        });
+    }
+
+    (*value).clone()
+}
+
+/// Registers a listener to a [Field] and returns its [FieldState]
+///
+/// See [ListenHandle::subscribe_state] for the transitions of the state. The listener is
+/// detached, when the component is unmounted.
+///
+/// ### Example
+///
+/// ```ignore
+/// #[function_component]
+/// fn Foo() -> Html {
+///     match tauri_interop::yew::use_field_state::<TestState, test_mod::FFoo>() {
+///         FieldState::Ready(foo) => html! { <h1>{foo}</h1> },
+///         _ => html! { <h1>{"loading..."}</h1> },
+///     }
+/// }
+/// ```
+#[cfg(feature = "event")]
+#[doc(cfg(feature = "event"))]
+#[hook]
+pub fn use_field_state<P, F>() -> FieldState<F::Type>
+where
+    P: Parent + 'static,
+    F: Field<P> + 'static,
+{
+    let state = use_state(|| FieldState::Loading);
+
+    {
+        let state = state.clone();
+        use_effect_with((), move |_| {
+            // holds the handle until the component is unmounted
+            let handle = Rc::new(RefCell::new(None));
+            let mounted = Rc::new(RefCell::new(true));
+
+            {
+                let handle = handle.clone();
+                let mounted = mounted.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    let listen_handle =
+                        ListenHandle::subscribe_state::<P, F>(move |update| state.set(update))
+                            .await;
+
+                    match listen_handle {
+                        Ok(listen_handle) if *mounted.borrow() => {
+                            handle.replace(Some(listen_handle));
+                        }
+                        // the component could be unmounted already, in which case the handle is dropped
+                        Ok(_) => {}
+                        Err(why) => log::error!("{why}"),
+                    }
+                });
+            }
+
+            move || {
+                mounted.replace(false);
+                handle.take();
+            }
+        });
+    }
+
+    (*state).clone()
+}
+
+/// The state of a command invoked by [use_command]
+struct CommandState<O> {
+    generation: u64,
+    loading: bool,
+    value: Option<Rc<O>>,
+}
+
+impl<O> Default for CommandState<O> {
+    fn default() -> Self {
+        Self {
+            generation: 0,
+            loading: true,
+            value: None,
+        }
+    }
+}
+
+enum CommandAction<O> {
+    Started(u64),
+    Completed(u64, O),
+}
+
+impl<O> Reducible for CommandState<O> {
+    type Action = CommandAction<O>;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        match action {
+            CommandAction::Started(generation) => Rc::new(Self {
+                generation,
+                loading: true,
+                value: self.value.clone(),
+            }),
+            // the output of an outdated invocation is discarded
+            CommandAction::Completed(generation, _) if generation != self.generation => self,
+            CommandAction::Completed(generation, output) => Rc::new(Self {
+                generation,
+                loading: false,
+                value: Some(Rc::new(output)),
+            }),
+        }
+    }
+}
+
+/// Handle returned by [use_command] to access the state of the command
+pub struct UseCommandHandle<O> {
+    state: UseReducerHandle<CommandState<O>>,
+    rerun: Rc<dyn Fn()>,
+}
+
+impl<O> Clone for UseCommandHandle<O> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            rerun: self.rerun.clone(),
+        }
+    }
+}
+
+impl<O> UseCommandHandle<O> {
+    /// Returns `true` while the command is invoked
+    pub fn loading(&self) -> bool {
+        self.state.loading
+    }
+
+    /// The output of the last completed invocation
+    ///
+    /// The previous output is kept while the command is invoked again.
+    pub fn output(&self) -> Option<&O> {
+        self.state.value.as_deref()
+    }
+
+    /// Invokes the command again with the current dependencies
+    pub fn rerun(&self) {
+        (self.rerun)()
+    }
+}
+
+impl<T, E> UseCommandHandle<Result<T, E>> {
+    /// The value of the last completed invocation, if it succeeded
+    pub fn data(&self) -> Option<&T> {
+        self.output().and_then(|output| output.as_ref().ok())
+    }
+
+    /// The error of the last completed invocation, if it failed
+    pub fn error(&self) -> Option<&E> {
+        self.output().and_then(|output| output.as_ref().err())
+    }
+}
+
+/// Invokes a command binding and tracks its loading state and output
+///
+/// The command is invoked when the component is mounted and every time the dependencies
+/// change. When the command returns a [Result], the handle provides the data and error
+/// separately.
+///
+/// ### Example
+///
+/// ```ignore
+/// #[function_component]
+/// fn Greeting(props: &GreetingProps) -> Html {
+///     let greeting = tauri_interop::yew::use_command(props.name.clone(), |name| {
+///         api::cmd::greet(name.clone())
+///     });
+///
+///     match greeting.output() {
+///         Some(greeting) => html! { <p>{greeting}</p> },
+///         None => html! { <p>{"loading..."}</p> },
+///     }
+/// }
+/// ```
+#[hook]
+pub fn use_command<D, O, C, Fut>(deps: D, command: C) -> UseCommandHandle<O>
+where
+    D: PartialEq + Clone + 'static,
+    O: 'static,
+    C: Fn(&D) -> Fut + 'static,
+    Fut: Future<Output = O> + 'static,
+{
+    let state = use_reducer(CommandState::<O>::default);
+    let generation = use_mut_ref(|| 0);
+    let command_ref = use_mut_ref(|| None::<Rc<dyn Fn(&D) -> Fut>>);
+    command_ref.replace(Some(Rc::new(command)));
+
+    let run = {
+        let state = state.dispatcher();
+        Rc::new(move |deps: &D| {
+            let Some(command) = command_ref.borrow().clone() else {
+                return;
+            };
+
+            let current = {
+                let mut generation = generation.borrow_mut();
+                *generation += 1;
+                *generation
+            };
+            state.dispatch(CommandAction::Started(current));
+
+            let future = command(deps);
+            let state = state.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let output = future.await;
+                state.dispatch(CommandAction::Completed(current, output))
+            });
+        })
+    };
+
+    {
+        let run = run.clone();
+        use_effect_with(deps.clone(), move |deps| run(deps));
+    }
+
+    UseCommandHandle {
+        state,
+        rerun: Rc::new(move || run(&deps)),
+    }
+}
@@ -1,15 +1,28 @@
 use proc_macro::TokenStream;
 
-use proc_macro2::Ident;
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use proc_macro_error::abort;
 use quote::{format_ident, quote, ToTokens};
-use syn::{parse_macro_input, punctuated::Punctuated, token::Comma, FnArg, ItemFn};
+use syn::{
+    parse_macro_input, parse_quote, punctuated::Punctuated, token::Comma, FnArg, ItemFn,
+    ReturnType, Type,
+};
 
+use crate::command::attributes::CommandAttributes;
 use crate::command::wrapper::{InvokeArgument, InvokeCommand};
 
+pub mod attributes;
+pub mod batch;
 pub mod collect;
+mod dioxus;
+pub mod job;
+mod leptos;
 mod wrapper;
 
-pub fn convert_to_binding(stream: TokenStream) -> TokenStream {
+pub fn convert_to_binding(
+    command_attributes: CommandAttributes,
+    stream: TokenStream,
+) -> TokenStream {
     let item_fn = parse_macro_input!(stream as ItemFn);
     let InvokeCommand {
         attributes,
@@ -23,10 +36,31 @@ pub fn convert_to_binding(stream: TokenStream) -> TokenStream {
     let InvokeArgument {
         argument_name,
         fields,
+        cancellation,
+        job_progress,
     } = invoke_argument;
 
     let async_ident = invoke.as_async();
-    let field_usage = fields
+    let output = match &return_type {
+        ReturnType::Default => parse_quote!(()),
+        ReturnType::Type(_, ty) => ty.as_ref().clone(),
+    };
+    if (command_attributes.leptos || command_attributes.dioxus) && async_ident.is_none() {
+        abort!(
+            name,
+            "only async commands or commands with a return type can be bound to a framework"
+        )
+    }
+
+    // the helpers are only generated, when the frontend is actually compiled with the framework
+    let command_name = name.to_string();
+    let invalidatable = command_attributes.is_invalidatable();
+    let leptos_helpers = (command_attributes.leptos && cfg!(feature = "leptos"))
+        .then(|| leptos::helpers(&name, &command_name, &fields, output.clone(), invalidatable));
+    let dioxus_helpers = (command_attributes.dioxus && cfg!(feature = "dioxus"))
+        .then(|| dioxus::helpers(&name, &command_name, &fields, output.clone()));
+
+    let mut field_usage = fields
         .iter()
         .map(|field| field.ident.clone())
         .collect::<Punctuated<Ident, Comma>>();
@@ -35,26 +69,161 @@ pub fn convert_to_binding(stream: TokenStream) -> TokenStream {
         .map(|field| field.argument)
         .collect::<Punctuated<FnArg, Comma>>();
 
-    let command_name = name.to_string();
     let args_ident = format_ident!("args");
-    let invoke_binding = invoke.as_expr(command_name, &args_ident);
+    let key_ident = format_ident!("cache_key");
+    let cache = command_attributes.cache.as_ref();
+    let invoke_binding = invoke.as_expr(
+        command_name.clone(),
+        &args_ident,
+        cache.map(|cache| (cache, &key_ident)),
+    );
+    // the key is built from the typed arguments, as their wasm value can contain maps
+    let cache_key =
+        cache.map(|_| quote!(let #key_ident = ::tauri_interop::command::cache::key(&#args_ident);));
+
+    let (dependencies, invalidate) = if command_attributes.is_invalidatable() {
+        if async_ident.is_none() {
+            abort!(
+                name,
+                "only async commands or commands with a return type can be cached"
+            )
+        }
+        if cancellation.is_some() {
+            abort!(name, "a command with a cancellation token can't be cached")
+        }
+
+        let parents = command_attributes
+            .invalidate_on
+            .iter()
+            .map(|dependent| &dependent.parent);
+        let fields = command_attributes
+            .invalidate_on
+            .iter()
+            .map(|dependent| &dependent.field);
+        let dependencies = (!command_attributes.invalidate_on.is_empty()).then(|| {
+            quote! {
+                ::tauri_interop::command::cache::invalidate_on(#command_name, &[
+                    #( <#fields as ::tauri_interop::event::SingleEventField<#parents>>::change_events() ),*
+                ]);
+            }
+        });
+
+        let invalidate = format_ident!("invalidate_{name}");
+        let doc = format!(" Invalidates the cached results of [`{name}`]");
+        let invalidate = quote! {
+            #[doc = #doc]
+            pub fn #invalidate() {
+                ::tauri_interop::command::cache::invalidate(#command_name)
+            }
+        };
+
+        (dependencies, invalidate)
+    } else {
+        (None, TokenStream2::new())
+    };
+
+    if command_attributes.job {
+        let progress = job_progress.unwrap_or(parse_quote!(()));
+
+        let stream = quote! {
+            #[derive(::tauri_interop::export::serde::Serialize, ::tauri_interop::export::serde::Deserialize)]
+            struct #argument_name #generics {
+                #field_definitions
+            }
+
+            #( #attributes )*
+            pub async fn #name #generics (#field_definitions) -> Result<
+                ::tauri_interop::command::job::JobHandle<#progress, #output>,
+                ::tauri_interop::command::job::JobError,
+            > {
+                let #args_ident = #argument_name { #field_usage };
+                let #args_ident = ::tauri_interop::export::serde_wasm_bindgen::to_value(&#args_ident)
+                    .expect("serialized arguments");
+
+                ::tauri_interop::command::job::JobHandle::start(#command_name, #args_ident).await
+            }
+        };
+
+        return TokenStream::from(stream.to_token_stream());
+    }
+
+    let mut argument_definitions = field_definitions.clone();
+    let (invocation_id, invoke_binding) = match cancellation {
+        Some(token) => {
+            argument_definitions.push(parse_quote!(#token: u32));
+            field_usage.push(token.clone());
+
+            let cancel_command = format_ident!("cancel_{name}");
+            let invoke_binding = if async_ident.is_some() {
+                quote! {
+                    let cancel_guard = ::tauri_interop::command::bindings::CancelGuard::new(
+                        move || #cancel_command(#token)
+                    );
+                    let result = #invoke_binding;
+                    cancel_guard.disarm();
+                    result
+                }
+            } else {
+                invoke_binding.to_token_stream()
+            };
+
+            let invocation_id =
+                quote!(let #token = ::tauri_interop::command::bindings::next_invocation_id(););
+            (invocation_id, invoke_binding)
+        }
+        None => (TokenStream2::new(), invoke_binding.to_token_stream()),
+    };
 
     let stream = quote! {
         #[derive(::tauri_interop::export::serde::Serialize, ::tauri_interop::export::serde::Deserialize)]
         struct #argument_name #generics {
-            #field_definitions
+            #argument_definitions
         }
 
         #( #attributes )*
         pub #async_ident fn #name #generics (#field_definitions) #return_type
         {
+            #dependencies
+            #invocation_id
             let #args_ident = #argument_name { #field_usage };
+            #cache_key
             let #args_ident = ::tauri_interop::export::serde_wasm_bindgen::to_value(&#args_ident)
                 .expect("serialized arguments");
 
             #invoke_binding
         }
+
+        #invalidate
+
+        #leptos_helpers
+
+        #dioxus_helpers
     };
 
     TokenStream::from(stream.to_token_stream())
 }
+
+/// Generates the command to cancel an invocation, when the command takes a cancellation token
+pub fn cancel_command(item_fn: &ItemFn) -> Option<TokenStream2> {
+    let takes_token = item_fn.sig.inputs.iter().any(|fn_arg| {
+        matches!(fn_arg, FnArg::Typed(typed)
+            if matches!(typed.ty.as_ref(), Type::Path(ty_path) if wrapper::is_cancellation(ty_path)))
+    });
+
+    if !takes_token {
+        return None;
+    }
+
+    let vis = &item_fn.vis;
+    let name = &item_fn.sig.ident;
+    let cancel_command = format_ident!("cancel_{name}");
+    let doc = format!(" Cancels a running invocation of [`{name}`]");
+
+    Some(quote! {
+        #[doc = #doc]
+        #[::tauri_interop::command]
+        #vis fn #cancel_command(webview: ::tauri_interop::command::TauriWebview, invocation_id: u32) {
+            ::tauri_interop::command::cancel_invocation(webview.label(), invocation_id)
+        }
+    })
+}
@@ -0,0 +1,153 @@
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use quote::quote;
+use syn::parse::{Parse, ParseStream, Parser};
+use syn::{punctuated::Punctuated, Expr, Lit, Meta, MetaNameValue, Path, Token, Type};
+
+/// Options provided to the command macro, e.g. `#[tauri_interop::command(job)]`
+#[derive(Default)]
+pub struct CommandAttributes {
+    pub job: bool,
+    pub cache: Option<Cache>,
+    pub invalidate_on: Vec<DependentField>,
+    pub leptos: bool,
+    pub dioxus: bool,
+}
+
+/// How the results of a command are shared between invocations in the frontend
+pub enum Cache {
+    /// `idempotent`: identical invocations share a pending request
+    Deduplicate,
+    /// `cache` or `cache(ttl = seconds)`: additionally, successful results are kept
+    Store(Option<f64>),
+}
+
+impl Cache {
+    /// The expression of the policy, which is passed to the binding
+    pub fn policy(&self) -> TokenStream {
+        match self {
+            Cache::Deduplicate => {
+                quote!(::tauri_interop::command::cache::CachePolicy::deduplicate())
+            }
+            Cache::Store(None) => quote!(::tauri_interop::command::cache::CachePolicy::cache(None)),
+            Cache::Store(Some(ttl)) => {
+                quote!(::tauri_interop::command::cache::CachePolicy::cache(Some(#ttl)))
+            }
+        }
+    }
+}
+
+/// A field, whose events invalidate the results of a command, e.g. `State => state::FField`
+///
+/// The parent is taken explicitly, as a field-struct can be a field of multiple parents.
+pub struct DependentField {
+    pub parent: Type,
+    pub field: Path,
+}
+
+impl Parse for DependentField {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let parent = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let field = input.parse()?;
+
+        Ok(Self { parent, field })
+    }
+}
+
+fn parse_ttl(stream: TokenStream) -> f64 {
+    let metas = Punctuated::<MetaNameValue, Token![,]>::parse_terminated
+        .parse2(stream)
+        .unwrap_or_else(|why| abort!(why.span(), "{}", why));
+
+    let mut ttl = None;
+    for meta in metas {
+        if !meta.path.is_ident("ttl") {
+            abort!(meta.path, "unknown cache attribute, expected `ttl`")
+        }
+
+        let seconds = match &meta.value {
+            Expr::Lit(expr) => match &expr.lit {
+                Lit::Int(int) => int.base10_parse::<u64>().ok().map(|ttl| ttl as f64),
+                Lit::Float(float) => float.base10_parse::<f64>().ok(),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        match seconds {
+            Some(seconds) if seconds >= 0.0 => ttl = Some(seconds),
+            _ => abort!(meta.value, "expected the ttl in seconds, e.g. `ttl = 30`"),
+        }
+    }
+
+    ttl.unwrap_or_else(|| abort!(proc_macro2::Span::call_site(), "expected `ttl = seconds`"))
+}
+
+impl CommandAttributes {
+    pub fn parse(stream: TokenStream) -> Self {
+        let metas = Punctuated::<Meta, Token![,]>::parse_terminated
+            .parse2(stream)
+            .unwrap_or_else(|why| abort!(why.span(), "{}", why));
+
+        let mut attributes = Self::default();
+        for meta in metas {
+            match &meta {
+                Meta::Path(path) if path.is_ident("job") => attributes.job = true,
+                Meta::Path(path) if path.is_ident("leptos") => attributes.leptos = true,
+                Meta::Path(path) if path.is_ident("dioxus") => attributes.dioxus = true,
+                Meta::Path(path) if path.is_ident("idempotent") => {
+                    attributes.cache.get_or_insert(Cache::Deduplicate);
+                }
+                Meta::Path(path) if path.is_ident("cache") => {
+                    attributes.cache = Some(Cache::Store(None))
+                }
+                Meta::List(list) if list.path.is_ident("cache") => {
+                    attributes.cache = Some(Cache::Store(Some(parse_ttl(list.tokens.clone()))))
+                }
+                Meta::List(list) if list.path.is_ident("invalidate_on") => {
+                    if !cfg!(feature = "event") {
+                        abort!(list.path, "`invalidate_on` requires the feature `event`")
+                    }
+
+                    let fields = Punctuated::<DependentField, Token![,]>::parse_terminated
+                        .parse2(list.tokens.clone())
+                        .unwrap_or_else(|why| {
+                            abort!(
+                                why.span(),
+                                "{}", why;
+                                help = "the fields are listed with their parent, e.g. `invalidate_on(State => state::FField)`"
+                            )
+                        });
+                    attributes.invalidate_on.extend(fields)
+                }
+                _ => abort!(meta, "unknown command attribute"),
+            }
+        }
+
+        if attributes.job
+            && (attributes.is_invalidatable() || attributes.leptos || attributes.dioxus)
+        {
+            abort!(
+                proc_macro2::Span::call_site(),
+                "a job command can't be cached, invalidated or bound to a framework"
+            )
+        }
+        if attributes.leptos
+            && attributes.dioxus
+            && cfg!(all(feature = "leptos", feature = "dioxus"))
+        {
+            abort!(
+                proc_macro2::Span::call_site(),
+                "the helpers of leptos and dioxus have the same name, only one can be used"
+            )
+        }
+
+        attributes
+    }
+
+    /// Whether the results of the command can be invalidated in the frontend
+    pub fn is_invalidatable(&self) -> bool {
+        self.cache.is_some() || !self.invalidate_on.is_empty()
+    }
+}
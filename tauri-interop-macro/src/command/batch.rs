@@ -0,0 +1,131 @@
+use convert_case::{Case, Casing};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{ItemFn, Type, Visibility};
+
+use crate::command::wrapper::{is_cancellation, typed_arguments};
+
+/// Generates the macro, which executes the command as part of a batch
+///
+/// Like the wrapper of `tauri::command`, the dispatcher is a macro, so that the runtime
+/// is inferred by the handler using it. The arguments are resolved the same way as for
+/// a separate invocation, the dispatcher of the batch is generated by `collect_commands`
+/// and `combine_handlers`.
+pub fn dispatcher(item_fn: &ItemFn) -> TokenStream {
+    let name = &item_fn.sig.ident;
+    let dispatcher = format_ident!("__batch_{name}");
+    let command_name = name.to_string();
+
+    // the types of the arguments are taken from the command, as they can't be named at
+    // the handler which uses the dispatcher
+    let arguments = typed_arguments(&item_fn.sig);
+    let argument_types = (0..arguments.len())
+        .map(|index| format_ident!("A{index}"))
+        .collect::<Vec<_>>();
+    let markers = (0..arguments.len())
+        .map(|index| format_ident!("marker_{index}"))
+        .collect::<Vec<_>>();
+    let argument_markers = (!arguments.is_empty()).then(|| {
+        quote! {
+            fn arguments<#( #argument_types, )* O>(
+                _: fn(#( #argument_types ),*) -> O,
+            ) -> (#( ::std::marker::PhantomData<#argument_types>, )*) {
+                (#( ::std::marker::PhantomData::<#argument_types>, )*)
+            }
+            #[allow(unused_variables)]
+            let (#( #markers, )*) = arguments($path);
+        }
+    });
+
+    let mut resolved = Vec::new();
+    let mut call_args = Vec::new();
+    for (index, (typed, pat_ident)) in arguments.into_iter().enumerate() {
+        let argument = format_ident!("argument_{index}");
+        let marker = &markers[index];
+        let key = pat_ident.ident.to_string().to_case(Case::Snake);
+        resolved.push(match typed.ty.as_ref() {
+            Type::Path(ty_path) if is_cancellation(ty_path) => quote! {
+                let #argument = match ::tauri_interop::command::batch::cancellation_token(
+                    message, &args, #command_name, #key
+                ) {
+                    Ok(argument) => argument,
+                    Err(why) => return Err(why),
+                };
+            },
+            _ => quote! {
+                let #argument = match (&::tauri_interop::command::batch::BatchArg::new(
+                    message, &args, #command_name, #key, #marker
+                ))
+                .resolve()
+                {
+                    Ok(argument) => argument,
+                    Err(why) => return Err(why),
+                };
+            },
+        });
+        call_args.push(argument);
+    }
+
+    let call = match item_fn.sig.asyncness {
+        Some(_) => quote!($path(#( #call_args ),*).await),
+        None => quote!($path(#( #call_args ),*)),
+    };
+    let response = quote! {
+        #argument_markers
+        #( #resolved )*
+        let value = #call;
+        (&value).batch_kind().response(value)
+    };
+
+    // like separate invocations, a synchronous command is executed immediately
+    let body = match item_fn.sig.asyncness {
+        Some(_) => quote! {
+            let message = ::std::clone::Clone::clone(message);
+            let response: ::tauri_interop::command::batch::BatchResponse = Box::pin(async move {
+                #[allow(unused_variables)]
+                let message = &message;
+                #response
+            });
+            response
+        },
+        None => quote! {
+            #[allow(clippy::redundant_closure_call)]
+            let response = (|| { #response })();
+            let response: ::tauri_interop::command::batch::BatchResponse =
+                Box::pin(::std::future::ready(response));
+            response
+        },
+    };
+
+    // macros used with `pub use` need to be exported, see `tauri::command`
+    let vis = &item_fn.vis;
+    let macro_export = match vis {
+        Visibility::Public(_) | Visibility::Restricted(_) => quote!(#[macro_export]),
+        Visibility::Inherited => TokenStream::new(),
+    };
+
+    quote! {
+        #[cfg(not(target_family = "wasm"))]
+        #[allow(unused_macros)]
+        #macro_export
+        #[doc(hidden)]
+        macro_rules! #dispatcher {
+            ($path:path, $message:expr, $args:expr) => {{
+                #[allow(unused_imports)]
+                use ::tauri_interop::command::batch::{
+                    FromBatchInvoke as _, FromBatchedCall as _, ResultKind as _, ValueKind as _,
+                };
+
+                #[allow(unused_variables)]
+                let message = $message;
+                #[allow(unused_variables)]
+                let args: ::tauri_interop::export::serde_json::Value = $args;
+                #body
+            }};
+        }
+
+        #[cfg(not(target_family = "wasm"))]
+        #[allow(unused_imports)]
+        #vis use #dispatcher;
+    }
+}
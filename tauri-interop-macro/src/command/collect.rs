@@ -5,7 +5,7 @@ use quote::{format_ident, quote};
 use syn::parse::Parser;
 use syn::punctuated::Punctuated;
 use syn::token::Comma;
-use syn::{parse_quote, ExprPath, ItemUse, Token};
+use syn::{parse_quote, Expr, ExprPath, ItemUse, Token};
 
 pub fn commands_with_mod_name(mod_name: &str, commands: &HashSet<String>) -> HashSet<String> {
     commands
@@ -61,6 +61,15 @@ pub fn get_handler_function(
     handlers: Punctuated<ExprPath, Comma>,
     include_mods: Vec<ExprPath>,
 ) -> TokenStream {
+    let dispatchers = commands.iter().map(|command| {
+        let path = command_to_expr_path(command);
+        let mut dispatcher = path.clone();
+        let segment = dispatcher.path.segments.last_mut().expect("a command path");
+        let command_name = segment.ident.to_string();
+        segment.ident = format_ident!("__batch_{}", segment.ident);
+
+        quote!(#command_name => Some(#dispatcher!(#path, message, args)))
+    });
     let commands = commands.iter().collect::<Vec<_>>();
     quote! {
         #[cfg(not(target_family = "wasm"))]
@@ -71,7 +80,13 @@ pub fn get_handler_function(
             let handlers = vec! [ #( #commands ),* ];
             ::tauri_interop::export::log::debug!("Registering following commands to tauri: {handlers:#?}");
 
-            ::tauri_interop::export::tauri::generate_handler![ #handlers ]
+            ::tauri_interop::command::batch::with_batching::<::tauri_interop::export::tauri::Wry>(
+                ::tauri_interop::export::tauri::generate_handler![ #handlers ],
+                |message, command, args| match command {
+                    #( #dispatchers, )*
+                    _ => None,
+                },
+            )
         }
     }
 }
@@ -83,3 +98,34 @@ pub fn uses(stream: proc_macro::TokenStream) -> Vec<ItemUse> {
         .into_iter()
         .collect::<Vec<_>>()
 }
+
+/// Generates the const holding the event names of the fields of a mod
+///
+/// The event names are passed to `collect_commands!` by the `Event` derive, or generated
+/// directly when it generates no getter commands. Every other mod holds no event names.
+pub fn event_names_const(event_names: Punctuated<Expr, Comma>) -> TokenStream {
+    quote! {
+        #[cfg(not(target_family = "wasm"))]
+        #[doc(hidden)]
+        pub const TAURI_INTEROP_EVENT_NAMES: &[&str] = &[ #event_names ];
+    }
+}
+
+/// Generates a const assertion, which fails when an event name is used in multiple mods or
+/// by multiple fields of a mod
+pub fn event_names_check(mods: &[ExprPath]) -> TokenStream {
+    quote! {
+        #[cfg(not(target_family = "wasm"))]
+        const _: () = {
+            const AN_EVENT_NAME_IS_USED_BY_MULTIPLE_FIELDS: () =
+                match ::tauri_interop::event::duplicate_event_name(&[
+                    #( #mods::TAURI_INTEROP_EVENT_NAMES ),*
+                ]) {
+                    Some(event_name) => panic!("{}", event_name),
+                    None => {}
+                };
+
+            AN_EVENT_NAME_IS_USED_BY_MULTIPLE_FIELDS
+        };
+    }
+}
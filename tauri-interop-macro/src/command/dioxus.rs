@@ -0,0 +1,35 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::Type;
+
+use crate::command::wrapper::FieldArg;
+
+/// Generates the hook `use_{name}_resource` for the binding of a command
+pub fn helpers(name: &Ident, command_name: &str, fields: &[FieldArg], output: Type) -> TokenStream {
+    let idents = fields.iter().map(|field| &field.ident).collect::<Vec<_>>();
+    let (types, call_args): (Vec<_>, Vec<_>) = fields.iter().map(FieldArg::owned).unzip();
+
+    let resource_fn = format_ident!("use_{name}_resource");
+    let resource_doc = format!(
+        " Creates a dioxus `Resource` of [`{name}`], which is restarted when the arguments change or the results are invalidated"
+    );
+
+    let (args_param, args_call) = if fields.is_empty() {
+        (TokenStream::new(), TokenStream::new())
+    } else {
+        (
+            quote!(args: impl Fn() -> ( #( #types, )* ) + 'static),
+            quote!(let ( #( #idents, )* ) = args();),
+        )
+    };
+
+    quote! {
+        #[doc = #resource_doc]
+        pub fn #resource_fn(#args_param) -> ::tauri_interop::export::dioxus::prelude::Resource<#output> {
+            ::tauri_interop::dioxus::use_command(#command_name, move || {
+                #args_call
+                async move { #name( #( #call_args ),* ).await }
+            })
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use quote::{format_ident, quote};
+use syn::{parse_quote, punctuated::Punctuated, token::Comma, FnArg, Ident, ItemFn, Pat, Type};
+
+use crate::command::batch;
+use crate::command::wrapper::{is_job_context, typed_arguments};
+
+/// Generates the host commands for a job command
+///
+/// The original function is renamed and spawned as job by the command named after
+/// the original function, which keeps the attributes of the function. Additionally, the
+/// commands `{name}_status`, `{name}_cancel`, `{name}_result` and `{name}_discard` are
+/// generated to control the job.
+pub fn host_commands(item_fn: ItemFn) -> TokenStream {
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = item_fn;
+
+    if sig.asyncness.is_none() {
+        abort!(sig.fn_token, "a job command has to be async")
+    }
+
+    let name = &sig.ident;
+    let context = format_ident!("tauri_interop_job");
+    let mut inputs = Punctuated::<FnArg, Comma>::new();
+    let mut call_args = Punctuated::<Ident, Comma>::new();
+    for (typed, pat_ident) in typed_arguments(&sig) {
+        if matches!(typed.ty.as_ref(), Type::Path(ty_path) if is_job_context(ty_path)) {
+            call_args.push(context.clone());
+            continue;
+        }
+
+        // the argument is only moved into the job, so it doesn't need to be mutable
+        let mut argument = typed.clone();
+        if let Pat::Ident(pat_ident) = argument.pat.as_mut() {
+            pat_ident.mutability = None;
+        }
+
+        inputs.push(FnArg::Typed(argument));
+        call_args.push(pat_ident.ident.clone());
+    }
+
+    let mut job_sig = sig.clone();
+    job_sig.ident = format_ident!("__job_{name}");
+    let job_name = &job_sig.ident;
+
+    let command_name = name.to_string();
+    let status_command = format_ident!("{name}_status");
+    let cancel_command = format_ident!("{name}_cancel");
+    let result_command = format_ident!("{name}_result");
+    let discard_command = format_ident!("{name}_discard");
+    let status_doc = format!(" Returns the status of a running [`{name}`] job");
+    let cancel_doc = format!(" Cancels a running [`{name}`] job");
+    let result_doc = format!(" Waits for a [`{name}`] job to complete and returns its result");
+    let discard_doc = format!(" Discards a [`{name}`] job, whose result isn't needed anymore");
+
+    let start_command: ItemFn = parse_quote! {
+        #vis fn #name(
+            tauri_interop_app_handle: ::tauri_interop::command::TauriAppHandle,
+            #inputs
+        ) -> ::tauri_interop::command::job::JobId {
+            ::tauri_interop::command::job::spawn(
+                tauri_interop_app_handle,
+                #command_name,
+                move |#context| #job_name(#call_args),
+            )
+        }
+    };
+    let batch_dispatcher = batch::dispatcher(&start_command);
+
+    quote! {
+        #[cfg(not(target_family = "wasm"))]
+        #( #attrs )*
+        #job_sig #block
+
+        #[cfg(not(target_family = "wasm"))]
+        #[::tauri_interop::export::tauri::command(root = "tauri_interop", rename_all = "snake_case")]
+        #start_command
+
+        #batch_dispatcher
+
+        #[cfg(not(target_family = "wasm"))]
+        #[doc = #status_doc]
+        #[::tauri_interop::command]
+        #vis fn #status_command(
+            job_id: ::tauri_interop::command::job::JobId,
+        ) -> Result<
+            ::tauri_interop::command::job::JobStatus<::tauri_interop::export::serde_json::Value>,
+            ::tauri_interop::command::job::JobError,
+        > {
+            ::tauri_interop::command::job::status(job_id)
+        }
+
+        #[cfg(not(target_family = "wasm"))]
+        #[doc = #cancel_doc]
+        #[::tauri_interop::command]
+        #vis fn #cancel_command(job_id: ::tauri_interop::command::job::JobId) {
+            ::tauri_interop::command::job::cancel(job_id)
+        }
+
+        #[cfg(not(target_family = "wasm"))]
+        #[doc = #result_doc]
+        #[::tauri_interop::command]
+        #vis async fn #result_command(
+            job_id: ::tauri_interop::command::job::JobId,
+        ) -> Result<::tauri_interop::export::serde_json::Value, ::tauri_interop::command::job::JobError> {
+            ::tauri_interop::command::job::result(job_id).await
+        }
+
+        #[cfg(not(target_family = "wasm"))]
+        #[doc = #discard_doc]
+        #[::tauri_interop::command]
+        #vis fn #discard_command(job_id: ::tauri_interop::command::job::JobId) {
+            ::tauri_interop::command::job::discard(job_id)
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::Type;
+
+use crate::command::wrapper::FieldArg;
+
+/// Generates `use_{name}_resource` and `{name}_action` for the binding of a command
+pub fn helpers(
+    name: &Ident,
+    command_name: &str,
+    fields: &[FieldArg],
+    output: Type,
+    invalidatable: bool,
+) -> TokenStream {
+    let idents = fields.iter().map(|field| &field.ident).collect::<Vec<_>>();
+    let (types, call_args): (Vec<_>, Vec<_>) = fields.iter().map(FieldArg::owned).unzip();
+
+    let resource_fn = format_ident!("use_{name}_resource");
+    let action_fn = format_ident!("{name}_action");
+    let resource_doc = format!(
+        " Creates a leptos `LocalResource` of [`{name}`], which is refetched when the arguments change"
+    );
+    let action_doc = format!(" Creates a leptos `Action` that dispatches [`{name}`]");
+
+    let (args_param, args_call) = if fields.is_empty() {
+        (TokenStream::new(), TokenStream::new())
+    } else {
+        (
+            quote!(args: impl Fn() -> ( #( #types, )* ) + 'static),
+            quote!(let ( #( #idents, )* ) = args();),
+        )
+    };
+
+    let refetch = invalidatable.then(|| {
+        quote! {
+            // the handle is dropped together with the owner of the resource
+            ::tauri_interop::export::leptos::prelude::StoredValue::new_local(
+                ::tauri_interop::command::cache::on_invalidate(#command_name, move || resource.refetch())
+            );
+        }
+    });
+
+    quote! {
+        #[doc = #resource_doc]
+        pub fn #resource_fn(#args_param) -> ::tauri_interop::export::leptos::prelude::LocalResource<#output> {
+            let resource = ::tauri_interop::export::leptos::prelude::LocalResource::new(move || {
+                #args_call
+                async move { #name( #( #call_args ),* ).await }
+            });
+            #refetch
+
+            resource
+        }
+
+        #[doc = #action_doc]
+        pub fn #action_fn() -> ::tauri_interop::export::leptos::prelude::Action<
+            ( #( #types, )* ),
+            #output,
+            ::tauri_interop::export::leptos::prelude::LocalStorage,
+        > {
+            ::tauri_interop::export::leptos::prelude::Action::new_local(|input: &( #( #types, )* )| {
+                let ( #( #idents, )* ) = ::std::clone::Clone::clone(input);
+                async move { #name( #( #call_args ),* ).await }
+            })
+        }
+    }
+}
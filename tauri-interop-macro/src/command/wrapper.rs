@@ -1,13 +1,17 @@
 use proc_macro::Span;
 
 use convert_case::{Case, Casing};
-use proc_macro2::Ident;
-use quote::{format_ident, ToTokens};
+use proc_macro2::{Ident, TokenStream};
+use proc_macro_error::abort;
+use quote::{format_ident, quote, ToTokens};
 use syn::{
-    parse_quote, Attribute, Expr, FnArg, GenericParam, Generics, ItemFn, Lifetime, LifetimeParam,
-    Pat, ReturnType, Signature, Type, TypePath,
+    parse_quote, Attribute, Expr, ExprCall, FnArg, GenericArgument, GenericParam, Generics, ItemFn,
+    Lifetime, LifetimeParam, Pat, PatIdent, PatType, PathArguments, ReturnType, Signature, Type,
+    TypePath,
 };
 
+use crate::command::attributes::Cache;
+
 #[derive(PartialEq)]
 pub enum Invoke {
     Empty,
@@ -21,7 +25,12 @@ impl Invoke {
         self.ne(&Invoke::Empty).then_some(format_ident!("async"))
     }
 
-    pub fn as_expr(&self, cmd_name: String, arg_name: &Ident) -> Expr {
+    pub fn as_expr(
+        &self,
+        cmd_name: String,
+        arg_name: &Ident,
+        cache: Option<(&Cache, &Ident)>,
+    ) -> Expr {
         let expr: Ident = match self {
             Invoke::Empty => parse_quote!(fire_and_forget_invoke),
             Invoke::AsyncEmpty => parse_quote!(wait_invoke),
@@ -29,12 +38,15 @@ impl Invoke {
             Invoke::AsyncResult => parse_quote!(catch_invoke),
         };
 
-        let call = parse_quote!( ::tauri_interop::command::bindings::#expr(#cmd_name, #arg_name) );
+        let call: ExprCall =
+            parse_quote!( ::tauri_interop::command::bindings::#expr(#cmd_name, #arg_name) );
 
-        if self.as_async().is_some() {
-            Expr::Await(parse_quote!(#call.await))
-        } else {
-            Expr::Call(call)
+        match cache.map(|(cache, key)| (cache.policy(), key)) {
+            Some((policy, key)) => Expr::Await(parse_quote!(
+                ::tauri_interop::command::cache::cached(#policy, #key, #call).await
+            )),
+            None if self.as_async().is_some() => Expr::Await(parse_quote!(#call.await)),
+            None => Expr::Call(call),
         }
     }
 }
@@ -65,6 +77,68 @@ fn new_arg_lt() -> Lifetime {
     Lifetime::new(ARGUMENT_LIFETIME, Span::call_site().into())
 }
 
+const CANCELLATION_TYPES: [&str; 2] = ["CancellationToken", "TauriCancellationToken"];
+
+const JOB_CONTEXT_TYPES: [&str; 2] = ["JobContext", "TauriJob"];
+
+fn last_segment_is(ty_path: &TypePath, names: &[&str]) -> bool {
+    ty_path
+        .path
+        .segments
+        .last()
+        .is_some_and(|segment| names.contains(&segment.ident.to_string().as_str()))
+}
+
+pub fn is_cancellation(ty_path: &TypePath) -> bool {
+    last_segment_is(ty_path, &CANCELLATION_TYPES)
+}
+
+pub fn is_job_context(ty_path: &TypePath) -> bool {
+    last_segment_is(ty_path, &JOB_CONTEXT_TYPES)
+}
+
+/// Returns the progress type of job context, which defaults to `()`
+fn job_progress(ty_path: &TypePath) -> Type {
+    let arguments = ty_path
+        .path
+        .segments
+        .last()
+        .map(|segment| &segment.arguments);
+    match arguments {
+        Some(PathArguments::AngleBracketed(generic)) => generic
+            .args
+            .iter()
+            .find_map(|argument| match argument {
+                GenericArgument::Type(ty) => Some(ty.clone()),
+                _ => None,
+            })
+            .unwrap_or(parse_quote!(())),
+        _ => parse_quote!(()),
+    }
+}
+
+/// Returns the arguments of a host command together with their identifier
+///
+/// Aborts on arguments, which can't be resolved by name.
+pub fn typed_arguments(sig: &Signature) -> Vec<(&PatType, &PatIdent)> {
+    sig.inputs
+        .iter()
+        .map(|fn_arg| {
+            let FnArg::Typed(typed) = fn_arg else {
+                abort!(fn_arg, "a command can't take self")
+            };
+            let Pat::Ident(pat_ident) = typed.pat.as_ref() else {
+                abort!(
+                    typed.pat,
+                    "the arguments of a command have to be identifiers"
+                )
+            };
+
+            (typed, pat_ident)
+        })
+        .collect()
+}
+
 fn any_tauri(ty_path: &TypePath) -> bool {
     ty_path
         .path
@@ -85,6 +159,8 @@ pub struct InvokeCommand {
 pub struct InvokeArgument {
     pub argument_name: Ident,
     pub fields: Vec<FieldArg>,
+    pub cancellation: Option<Ident>,
+    pub job_progress: Option<Type>,
 }
 
 pub struct FieldArg {
@@ -93,6 +169,29 @@ pub struct FieldArg {
     requires_lifetime: bool,
 }
 
+impl FieldArg {
+    /// Returns the owned type of the argument and the expression to pass it to the binding
+    ///
+    /// References can't be held by framework helpers, so they are replaced by their owned type.
+    pub fn owned(&self) -> (Type, TokenStream) {
+        let ident = &self.ident;
+        let FnArg::Typed(typed) = &self.argument else {
+            unreachable!("the fields of a binding are typed")
+        };
+
+        match typed.ty.as_ref() {
+            Type::Reference(reference) => {
+                let elem = &reference.elem;
+                (
+                    parse_quote!(<#elem as ::std::borrow::ToOwned>::Owned),
+                    quote!(::std::borrow::Borrow::borrow(&#ident)),
+                )
+            }
+            ty => (ty.clone(), quote!(#ident)),
+        }
+    }
+}
+
 pub fn prepare(function: ItemFn) -> InvokeCommand {
     let ItemFn {
         attrs: attributes,
@@ -109,6 +208,8 @@ pub fn prepare(function: ItemFn) -> InvokeCommand {
         ..
     } = sig;
 
+    let mut cancellation = None;
+    let mut progress = None;
     let filtered_fields = inputs
         .into_iter()
         .filter_map(|mut fn_arg| {
@@ -117,6 +218,25 @@ pub fn prepare(function: ItemFn) -> InvokeCommand {
                 _ => return None,
             };
 
+            // the token is replaced by an invocation id, which is provided by the binding itself
+            if matches!(typed.ty.as_ref(), Type::Path(ty_path) if is_cancellation(ty_path)) {
+                if let Pat::Ident(ident) = typed.pat.as_ref() {
+                    cancellation = Some(format_ident!(
+                        "{}",
+                        ident.ident.to_string().to_case(Case::Snake)
+                    ));
+                }
+                return None;
+            }
+
+            // the context of a job is provided by the host
+            if let Type::Path(ty_path) = typed.ty.as_ref() {
+                if is_job_context(ty_path) {
+                    progress = Some(job_progress(ty_path));
+                    return None;
+                }
+            }
+
             if matches!(typed.ty.as_ref(), Type::Path(ty_path) if any_tauri(ty_path)) {
                 return None;
             }
@@ -161,6 +281,8 @@ pub fn prepare(function: ItemFn) -> InvokeCommand {
         invoke_argument: InvokeArgument {
             argument_name,
             fields: filtered_fields,
+            cancellation,
+            job_progress: progress,
         },
     }
 }
@@ -1,47 +1,274 @@
 use convert_case::{Case, Casing};
-use proc_macro2::Ident;
-use quote::format_ident;
-use syn::{Attribute, Data, DeriveInput, Type};
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote, ToTokens};
+use syn::parse::ParseStream;
+use syn::punctuated::Punctuated;
+use syn::{
+    parenthesized, Attribute, Data, DeriveInput, Expr, Fields, Generics, Index, LitStr, Member,
+    Meta, Path, PathArguments, Token, Type, Variant, WhereClause,
+};
 
 pub(crate) mod emit;
+pub(crate) mod host;
 pub(crate) mod listen;
 
 struct EventStruct {
     name: Ident,
     mod_name: Ident,
     fields: Vec<EventField>,
+    all_field: Option<Ident>,
+    /// The event name of the `all_field`
+    all_event_name: TokenStream,
+    to_host: bool,
+    key: Option<Member>,
+    /// The variants of an enum, which is emitted as a whole using the `all_field`
+    variants: Vec<Variant>,
+    generics: Generics,
+    /// The instantiations of a generic struct and their tags
+    tags: Vec<(Type, String)>,
+    /// `true` when the struct can be nested into other structs with `#[event(nestable)]`
+    nestable: bool,
 }
 
 struct EventField {
     field_name: Ident,
-    parent_field_name: Ident,
+    parent_field_name: Member,
     parent_field_ty: Type,
+    event_name: TokenStream,
+    options: FieldOptions,
+    nested: Option<Nested>,
 }
 
-fn prepare_event(derive_input: DeriveInput) -> EventStruct {
-    let data_struct = match derive_input.data {
-        Data::Struct(data_struct) => data_struct,
-        _ => panic!("The macro only works with structs"),
-    };
+/// A field marked with `#[event(nested)]`
+///
+/// The fields of the nested struct are generated as field-structs in a mod named after the
+/// field, using the `nested_fields!` macro of the nested struct.
+struct Nested {
+    mod_name: Ident,
+    /// The path to the mod of the nested struct, relative to the generated mod
+    path: Path,
+    /// The prefix of the event names of the nested fields
+    prefix: TokenStream,
+}
+
+/// Options of a field given with `#[event(...)]`
+#[derive(Default)]
+struct FieldOptions {
+    writable: bool,
+    /// The event name given with `#[event(name = "...")]`
+    name: Option<LitStr>,
+    /// `true` when the field is excluded with `#[event(skip)]`
+    skip: bool,
+    /// The name given with `#[event(rename = "...")]`, used instead of the name of the field
+    rename: Option<String>,
+    /// `true` when the getter command is suppressed with `#[event(no_getter)]`
+    no_getter: bool,
+    /// Given with `#[event(nested)]` or `#[event(nested = path)]`, the latter naming the mod of
+    /// the nested struct
+    nested: Option<Option<Path>>,
+    /// `true` when the changes of the field are emitted as deltas with `#[event(collection)]`
+    collection: bool,
+}
+
+/// How the event names of a struct are built
+enum EventNaming {
+    /// The module path of the field-struct followed by its name (default)
+    ModulePath,
+    /// The given prefix followed by the struct and field name, see `#[event(prefix = "...")]`
+    Prefix(LitStr),
+    /// The given name followed by the field name, see `#[event(name = "...")]`
+    Name(LitStr),
+}
+
+impl EventNaming {
+    /// Returns the event name of a field as expression, which evaluates to a `&'static str`
+    ///
+    /// Fails when the event name contains characters, which tauri doesn't accept.
+    fn event_name(
+        &self,
+        parent: &Ident,
+        field_name: &Ident,
+        name: Option<&LitStr>,
+    ) -> syn::Result<TokenStream> {
+        let (event_name, given) = match (name, self) {
+            (Some(name), _) => (name.value(), name),
+            (None, Self::ModulePath) => {
+                let field_name = field_name.to_string();
+                return Ok(quote!(concat!(module_path!(), "::", #field_name)));
+            }
+            (None, Self::Prefix(prefix)) if prefix.value().is_empty() => {
+                (format!("{parent}::{field_name}"), prefix)
+            }
+            (None, Self::Prefix(prefix)) => (
+                format!("{}::{parent}::{field_name}", prefix.value()),
+                prefix,
+            ),
+            (None, Self::Name(name)) => (format!("{}::{field_name}", name.value()), name),
+        };
+
+        // tauri only accepts these characters in event names
+        if !event_name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '/' | ':' | '_'))
+        {
+            return Err(syn::Error::new_spanned(
+                given,
+                format!("The event name {event_name:?} may only contain alphanumeric characters, `-`, `/`, `:` and `_`"),
+            ));
+        }
 
-    if data_struct.fields.is_empty() {
-        panic!("No fields provided")
+        Ok(quote!(#event_name))
     }
+}
 
-    if data_struct.fields.iter().any(|field| field.ident.is_none()) {
-        panic!("Tuple Structs aren't supported")
+fn parse_field_options(attrs: &[Attribute]) -> syn::Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("event")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("writable") {
+                if !cfg!(feature = "initial_value") {
+                    return Err(meta.error("Writable fields require the feature `initial_value`"));
+                }
+                options.writable = true;
+                Ok(())
+            } else if meta.path.is_ident("name") {
+                options.name = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else if meta.path.is_ident("skip") {
+                options.skip = true;
+                Ok(())
+            } else if meta.path.is_ident("rename") {
+                options.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else if meta.path.is_ident("no_getter") {
+                options.no_getter = true;
+                Ok(())
+            } else if meta.path.is_ident("nested") {
+                let path = match meta.input.peek(Token![=]) {
+                    true => Some(meta.value()?.parse::<Path>()?),
+                    false => None,
+                };
+                options.nested = Some(path);
+                Ok(())
+            } else if meta.path.is_ident("collection") {
+                options.collection = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown event attribute"))
+            }
+        })?;
     }
 
+    Ok(options)
+}
+
+/// Options of a struct given with `#[event(...)]`
+#[derive(Default)]
+struct EventOptions {
+    /// `true` when marked with `#[event(direction = "to_host")]`
+    to_host: bool,
+    /// The field given with `#[event(key = field)]`, which identifies an instance
+    key: Option<Member>,
+    /// The prefix given with `#[event(prefix = "...")]`
+    prefix: Option<LitStr>,
+    /// The name given with `#[event(name = "...")]`
+    name: Option<LitStr>,
+    /// The instantiations of a generic struct given with `#[event(tag(Type<...> = "..."))]`
+    tags: Vec<(Type, String)>,
+    /// Given with `#[event(nestable)]`, see `EventStruct::derive_nested_macro`
+    nestable: Option<Path>,
+}
+
+fn parse_event_options(attrs: &[Attribute]) -> syn::Result<EventOptions> {
+    let mut options = EventOptions::default();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("event")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("direction") {
+                let direction = meta.value()?.parse::<LitStr>()?;
+                match direction.value().as_str() {
+                    "to_host" => options.to_host = true,
+                    "to_frontend" => options.to_host = false,
+                    _ => return Err(meta.error("expected \"to_host\" or \"to_frontend\"")),
+                }
+                Ok(())
+            } else if meta.path.is_ident("key") {
+                options.key = Some(meta.value()?.parse::<Member>()?);
+                Ok(())
+            } else if meta.path.is_ident("prefix") {
+                options.prefix = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else if meta.path.is_ident("name") {
+                options.name = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else if meta.path.is_ident("tag") {
+                let content;
+                parenthesized!(content in meta.input);
+                let ty = content.parse::<Type>()?;
+                content.parse::<Token![=]>()?;
+                let tag = content.parse::<LitStr>()?;
+                if !tag.value().chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    return Err(syn::Error::new_spanned(
+                        tag,
+                        "A tag may only contain alphanumeric characters and `_`",
+                    ));
+                }
+                options.tags.push((ty, tag.value()));
+                Ok(())
+            } else if meta.path.is_ident("nestable") {
+                options.nestable = Some(meta.path);
+                Ok(())
+            } else {
+                Err(meta.error("unknown event attribute"))
+            }
+        })?;
+    }
+
+    Ok(options)
+}
+
+fn prepare_event(derive_input: DeriveInput) -> syn::Result<EventStruct> {
+    // an enum is emitted as a whole, so it has no fields but the `all_field`
+    let (data_fields, variants) = match derive_input.data {
+        Data::Struct(data_struct) if data_struct.fields.is_empty() => {
+            return Err(syn::Error::new_spanned(
+                &derive_input.ident,
+                "No fields provided",
+            ))
+        }
+        Data::Struct(data_struct) => (data_struct.fields, Vec::new()),
+        Data::Enum(data_enum) if data_enum.variants.is_empty() => {
+            return Err(syn::Error::new_spanned(
+                &derive_input.ident,
+                "No variants provided",
+            ))
+        }
+        Data::Enum(data_enum) => (Fields::Unit, data_enum.variants.into_iter().collect()),
+        Data::Union(data_union) => {
+            return Err(syn::Error::new_spanned(
+                data_union.union_token,
+                "The macro only works with structs and enums",
+            ))
+        }
+    };
+
     let auto_naming = derive_input
         .attrs
         .iter()
         .find(|attr| attr.path().is_ident("auto_naming"))
-        .map(|attr| attr.parse_args::<Ident>().unwrap().to_string());
+        .map(|attr| attr.parse_args::<Ident>())
+        .transpose()?;
 
     let name = derive_input.ident.clone();
     let (naming_case, mod_name) = match auto_naming {
         Some(naming) if naming == "EnumLike" => (Case::Pascal, format!("{name}Field")),
-        Some(naming) => panic!("No naming type found for: {naming}"),
+        Some(naming) => {
+            return Err(syn::Error::new_spanned(
+                &naming,
+                format!("No naming type found for: {naming}, expected `EnumLike`"),
+            ))
+        }
         None => (Case::Snake, name.to_string()),
     };
 
@@ -49,42 +276,614 @@ fn prepare_event(derive_input: DeriveInput) -> EventStruct {
         .attrs
         .iter()
         .find(|attr| attr.path().is_ident("mod_name"))
-        .map(|attr| attr.parse_args::<Ident>().unwrap())
+        .map(|attr| attr.parse_args::<Ident>())
+        .transpose()?
         .unwrap_or(format_ident!("{}", mod_name.to_case(naming_case)));
 
-    let fields = data_struct
-        .fields
+    let all_field = derive_input
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("all_field"))
+        .map(|attr| match &attr.meta {
+            Meta::Path(_) => Ok(format_ident!("All")),
+            _ => attr.parse_args::<Ident>(),
+        })
+        .transpose()?
+        .or_else(|| (!variants.is_empty()).then(|| format_ident!("All")));
+
+    let EventOptions {
+        to_host,
+        key,
+        prefix,
+        name: event_name,
+        tags,
+        nestable,
+    } = parse_event_options(&derive_input.attrs)?;
+
+    let generics = derive_input.generics;
+    if let Some(lifetime) = generics.lifetimes().next() {
+        return Err(syn::Error::new_spanned(
+            lifetime,
+            "Lifetimes aren't supported, the fields have to be self owned",
+        ));
+    }
+    if let Some(nestable) = &nestable {
+        if to_host || key.is_some() || !generics.params.is_empty() || !variants.is_empty() {
+            return Err(syn::Error::new_spanned(
+                nestable,
+                "Only structs with events to the frontend can be nestable, without instance keys or generics",
+            ));
+        }
+    }
+    if generics.params.is_empty() != tags.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &name,
+            "Generic structs require at least one `#[event(tag(...))]`, which are only allowed for generic structs",
+        ));
+    }
+
+    let naming = match (prefix, event_name) {
+        (Some(_), Some(name)) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "The attributes `prefix` and `name` are exclusive",
+            ))
+        }
+        (Some(prefix), None) => EventNaming::Prefix(prefix),
+        (None, Some(name)) => EventNaming::Name(name),
+        (None, None) => EventNaming::ModulePath,
+    };
+
+    let fields = data_fields
         .iter()
-        .map(|field| {
-            let field_ident = field.ident.as_ref().unwrap();
-            let field_name = format_ident!("F{}", field_ident.to_string().to_case(Case::Pascal));
+        .zip(0..)
+        .map(|(field, index)| {
+            parse_field_options(&field.attrs).map(|options| (field, index, options))
+        })
+        .filter(|parsed| !matches!(parsed, Ok((_, _, options)) if options.skip))
+        .map(|parsed| {
+            let (field, index, mut options) = parsed?;
+            // fields of tuple structs are named after their index
+            let parent_field_name = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(Index::from(index)),
+            };
+            let field_name = options
+                .rename
+                .clone()
+                .unwrap_or_else(|| match &field.ident {
+                    Some(ident) => ident.to_string(),
+                    None => index.to_string(),
+                });
+            let field_name = format_ident!("F{}", field_name.to_case(Case::Pascal));
+            let event_name = naming.event_name(&name, &field_name, options.name.as_ref())?;
+
+            let nested = match options.nested.take() {
+                Some(path) => {
+                    // by default the events of nested fields are named by the path of their field-struct
+                    let prefix = match (&naming, &options.name) {
+                        (EventNaming::ModulePath, None) => quote!(module_path!()),
+                        _ => event_name.clone(),
+                    };
+                    let mod_name = field_name.to_string();
+                    let mod_name = (&mod_name[1..]).to_case(Case::Snake);
+                    let mod_name = match mod_name.starts_with(|c: char| c.is_ascii_digit()) {
+                        true => format_ident!("f{mod_name}"),
+                        false => format_ident!("{mod_name}"),
+                    };
+
+                    Some(Nested {
+                        mod_name,
+                        path: nested_path(path, &field.ty)?,
+                        prefix,
+                    })
+                }
+                None => None,
+            };
 
-            EventField {
+            Ok(EventField {
+                event_name,
                 field_name,
-                parent_field_name: field_ident.clone(),
+                parent_field_name,
                 parent_field_ty: field.ty.clone(),
-            }
+                options,
+                nested,
+            })
         })
-        .collect::<Vec<_>>();
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let all_event_name = all_field
+        .as_ref()
+        .map(|all_field| naming.event_name(&name, all_field, None))
+        .transpose()?
+        .unwrap_or_default();
+
+    if !generics.params.is_empty() {
+        let unsupported = match (to_host, &key) {
+            (true, _) => Some("events to the host"),
+            (_, Some(_)) => Some("instance keys"),
+            _ if fields.iter().any(|field| field.options.writable) => Some("writable fields"),
+            _ => None,
+        };
+        if let Some(unsupported) = unsupported {
+            return Err(syn::Error::new_spanned(
+                &generics,
+                format!("Generic structs don't support {unsupported}"),
+            ));
+        }
+    }
 
-    EventStruct {
+    if let Some(field) = fields.iter().find(|field| field.nested.is_some()) {
+        if to_host || key.is_some() || !generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &field.parent_field_ty,
+                "Nested fields aren't supported for events to the host, instance keys or generic structs",
+            ));
+        }
+    }
+
+    if let Some(field) = fields.iter().find(|field| field.options.collection) {
+        if to_host || key.is_some() || !generics.params.is_empty() || field.nested.is_some() {
+            return Err(syn::Error::new_spanned(
+                &field.parent_field_ty,
+                "Collection fields aren't supported for events to the host, instance keys, generic structs or nested fields",
+            ));
+        }
+    }
+
+    if to_host {
+        if let Some(field) = fields.iter().find(|field| field.options.writable) {
+            return Err(syn::Error::new_spanned(
+                &field.parent_field_name,
+                "Writable fields aren't supported for events to the host",
+            ));
+        }
+    }
+
+    if let Some(key) = &key {
+        if to_host {
+            return Err(syn::Error::new_spanned(
+                key,
+                "Instance keys aren't supported for events to the host",
+            ));
+        }
+        if !fields.iter().any(|field| &field.parent_field_name == key) {
+            return Err(syn::Error::new_spanned(
+                key,
+                "The instance key isn't a field of the struct",
+            ));
+        }
+        if let Some(field) = fields.iter().find(|field| field.options.writable) {
+            return Err(syn::Error::new_spanned(
+                &field.parent_field_name,
+                "Writable fields aren't supported for events with an instance key",
+            ));
+        }
+    }
+
+    Ok(EventStruct {
         name,
         mod_name,
         fields,
+        all_field,
+        all_event_name,
+        to_host,
+        key,
+        variants,
+        generics,
+        tags,
+        nestable: nestable.is_some(),
+    })
+}
+
+impl EventStruct {
+    /// The attribute passing the generics of a generic struct to its field-structs
+    fn parent_generics_attr(&self) -> Option<TokenStream> {
+        if self.generics.params.is_empty() {
+            return None;
+        }
+
+        let generics = &self.generics;
+        let where_clause = &generics.where_clause;
+        Some(quote!(#[parent_generics(#generics #where_clause)]))
+    }
+
+    /// The field-structs together with their type and event name, including the `all_field`
+    fn field_structs(&self) -> Vec<(&Ident, TokenStream, &TokenStream)> {
+        let name = &self.name;
+        let (_, ty_generics, _) = self.generics.split_for_impl();
+
+        self.fields
+            .iter()
+            .map(|field| {
+                let parent_field_ty = &field.parent_field_ty;
+                (
+                    &field.field_name,
+                    quote!(#parent_field_ty),
+                    &field.event_name,
+                )
+            })
+            .chain(
+                self.all_field
+                    .iter()
+                    .map(|all_field| (all_field, quote!(#name #ty_generics), &self.all_event_name)),
+            )
+            .collect()
+    }
+
+    /// The event names of all field-structs, for each tag of a generic struct
+    ///
+    /// Passed to `collect_commands!`, so that `combine_handlers!` can check for duplicates.
+    fn event_names(&self) -> Vec<TokenStream> {
+        let field_structs = self.field_structs();
+        if self.tags.is_empty() {
+            return field_structs
+                .into_iter()
+                .map(|(_, _, event_name)| event_name.clone())
+                .collect();
+        }
+
+        self.tags
+            .iter()
+            .flat_map(|(_, tag)| {
+                field_structs
+                    .iter()
+                    .map(move |(_, _, event_name)| quote!(concat!(#event_name, "::", #tag)))
+            })
+            .collect()
+    }
+
+    /// The where clause of a generic `Emit` or `Listen` impl
+    ///
+    /// Requires the instantiation to be tagged and the type of each field to be exchangeable.
+    fn generic_where_clause(&self) -> Option<WhereClause> {
+        if self.generics.params.is_empty() {
+            return self.generics.where_clause.clone();
+        }
+
+        let mod_name = &self.mod_name;
+        let mut generics = self.generics.clone();
+        let where_clause = generics.make_where_clause();
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(Self: #mod_name::Tag));
+        for (_, field_ty, _) in self.field_structs() {
+            where_clause.predicates.push(syn::parse_quote! {
+                #field_ty: Clone
+                    + ::tauri_interop::export::serde::Serialize
+                    + ::tauri_interop::export::serde::de::DeserializeOwned
+                    + 'static
+            });
+        }
+
+        generics.where_clause
+    }
+
+    /// Generates the `Tag` trait of a generic struct and implements it for each given tag
+    ///
+    /// The trait provides the event names of the instantiations, as these can't be
+    /// concatenated in the generic `Field` implementations. When listening with feature
+    /// `initial_value`, a getter binding is generated for each tag and field, which is
+    /// provided by the trait as well.
+    fn derive_tags(&self, listen: bool) -> Option<TokenStream> {
+        if self.tags.is_empty() {
+            return None;
+        }
+
+        let name = &self.name;
+        let field_structs = self.field_structs();
+        let field_names = field_structs.iter().map(|(field_name, ..)| field_name);
+        let doc = format!(
+            " Tags the instantiations of [{}], so that their events don't collide",
+            self.name
+        );
+
+        // the value of an instance is only available with its key, see `InstanceField`
+        let getter_fields = match listen && cfg!(feature = "initial_value") && self.key.is_none() {
+            true => self
+                .fields
+                .iter()
+                .filter(|field| !field.options.no_getter)
+                .map(|field| &field.field_name)
+                .chain(self.all_field.iter())
+                .collect(),
+            false => Vec::new(),
+        };
+        let getter_type = |field_name: &Ident, parent: TokenStream| {
+            quote! {
+                Result<
+                    Option<::tauri_interop::event::Revisioned<<#field_name as ::tauri_interop::event::Field<#parent>>::Type>>,
+                    ::tauri_interop::event::EventError,
+                >
+            }
+        };
+
+        let getter_fns = getter_fields.iter().map(|field_name| {
+            let getter = format_ident!("get_{field_name}");
+            let getter_type = getter_type(field_name, quote!(Self));
+
+            quote! {
+                #[doc(hidden)]
+                #[allow(async_fn_in_trait)]
+                async fn #getter() -> #getter_type
+                where
+                    Self: Sized + ::tauri_interop::event::Parent,
+                    #field_name: ::tauri_interop::event::Field<Self>;
+            }
+        });
+
+        // the getter commands are suffixed by the tag, see `emit::derive_tag_getters`
+        let get_cmd =
+            |field_name: &Ident, tag: &str| format_ident!("get_{name}_{field_name}_{tag}");
+        let get_cmd_fns = self.tags.iter().flat_map(|(ty, tag)| {
+            getter_fields.iter().map(move |field_name| {
+                let get_cmd = get_cmd(field_name, tag);
+                let getter_type = getter_type(field_name, quote!(#ty));
+
+                quote! {
+                    #[allow(non_snake_case)]
+                    #[tauri_interop::command]
+                    pub fn #get_cmd() -> #getter_type {}
+                }
+            })
+        });
+
+        let impls = self.tags.iter().map(|(ty, tag)| {
+            let event_names = field_structs.iter().map(|(field_name, _, event_name)| {
+                quote!(const #field_name: &'static str = concat!(#event_name, "::", #tag);)
+            });
+
+            let getters = getter_fields.iter().map(|field_name| {
+                let getter = format_ident!("get_{field_name}");
+                let get_cmd = get_cmd(field_name, tag);
+                let getter_type = getter_type(field_name, quote!(Self));
+
+                quote! {
+                    async fn #getter() -> #getter_type
+                    where
+                        Self: Sized + ::tauri_interop::event::Parent,
+                        #field_name: ::tauri_interop::event::Field<Self>,
+                    {
+                        #get_cmd().await
+                    }
+                }
+            });
+
+            quote! {
+                #[allow(non_upper_case_globals)]
+                impl Tag for #ty {
+                    const TAG: &'static str = #tag;
+
+                    #( #event_names )*
+
+                    #( #getters )*
+                }
+            }
+        });
+
+        Some(quote! {
+            #[doc = #doc]
+            #[allow(non_upper_case_globals)]
+            pub trait Tag {
+                /// The tag of the instantiation, which is appended to its event names
+                const TAG: &'static str;
+
+                #(
+                    #[doc(hidden)]
+                    const #field_names: &'static str;
+                )*
+
+                #( #getter_fns )*
+            }
+
+            #( #impls )*
+
+            #( #get_cmd_fns )*
+        })
+    }
+
+    /// Generates a mod for each nested field, containing the field-structs of the nested struct
+    fn derive_nested_mods(&self) -> TokenStream {
+        let name = &self.name;
+        let nested_mods = self.fields.iter().filter_map(|field| {
+            let Nested {
+                mod_name,
+                path,
+                prefix,
+            } = field.nested.as_ref()?;
+            let parent_field_name = &field.parent_field_name;
+            let field_name = &field.field_name;
+            let doc = format!(" The nested fields of [{field_name}]");
+
+            Some(quote! {
+                #[doc = #doc]
+                pub mod #mod_name {
+                    use super::*;
+
+                    #path::nested_fields!(#name, #parent_field_name, [#path], (#prefix), #field_name);
+                }
+            })
+        });
+
+        quote!( #( #nested_mods )* )
+    }
+
+    /// Generates the `nested_fields!` macro, which generates the field-structs of the struct,
+    /// when it is nested into a field of another struct with `#[event(nested)]`
+    ///
+    /// Only generated for structs marked with `#[event(nestable)]`. The macro is invoked with the
+    /// parent, the member of the nested field, the path to this mod, the prefix of the event names
+    /// and the field-struct of the nested field. The types of the fields are aliased in
+    /// `nested_types`, as they can't be named at the invocation. As the macro is only exported
+    /// within the crate, structs can't be nested across crates.
+    fn derive_nested_macro(&self, listen: bool) -> Option<TokenStream> {
+        if !self.nestable {
+            return None;
+        }
+
+        let types = self.fields.iter().map(|field| {
+            let EventField {
+                field_name,
+                parent_field_ty,
+                ..
+            } = field;
+            quote!(pub type #field_name = #parent_field_ty;)
+        });
+
+        let field_structs = self.fields.iter().map(|field| {
+            let EventField {
+                field_name,
+                parent_field_name,
+                ..
+            } = field;
+            let event_name = field_name.to_string();
+
+            // the value of a nested field is acquired by the getter of the field it is part of
+            match listen {
+                true => quote! {
+                    #[allow(dead_code)]
+                    #[derive(::tauri_interop::ListenField)]
+                    #[parent($parent)]
+                    #[parent_field_ty($($path)*::nested_types::#field_name)]
+                    #[event_name(concat!($($prefix)*, "::", #event_name))]
+                    #[nested_in(super::$nested_in, #parent_field_name)]
+                    pub struct #field_name;
+                },
+                false => quote! {
+                    #[allow(dead_code)]
+                    #[derive(::tauri_interop::EmitField)]
+                    #[parent($parent)]
+                    #[parent_field_name($member . #parent_field_name)]
+                    #[parent_field_ty($($path)*::nested_types::#field_name)]
+                    #[event_name(concat!($($prefix)*, "::", #event_name))]
+                    #[no_getter]
+                    pub struct #field_name;
+                },
+            }
+        });
+
+        let nested_fields = listen.then(|| {
+            let nested_fields = self.fields.iter().map(|field| {
+                let EventField {
+                    field_name,
+                    parent_field_name,
+                    ..
+                } = field;
+
+                quote! {
+                    ::tauri_interop::event::NestedField::new(
+                        <#field_name as ::tauri_interop::event::Field<$parent>>::EVENT_NAME,
+                        |parent: &mut Nested, value| parent.#parent_field_name = value,
+                    )
+                }
+            });
+
+            quote! {
+                /// The fields composed into the value of the nested field
+                pub fn nested_fields() -> Vec<::tauri_interop::event::NestedField<<super::$nested_in as ::tauri_interop::event::Field<$parent>>::Type>> {
+                    type Nested = <super::$nested_in as ::tauri_interop::event::Field<$parent>>::Type;
+
+                    vec![ #( #nested_fields ),* ]
+                }
+            }
+        });
+
+        Some(quote! {
+            #[doc(hidden)]
+            pub mod nested_types {
+                use super::*;
+
+                #( #types )*
+            }
+
+            #[doc(hidden)]
+            #[allow(unused_macros)]
+            macro_rules! nested_fields {
+                ($parent:ident, $member:tt, [$($path:tt)*], ($($prefix:tt)*), $nested_in:ident) => {
+                    #( #field_structs )*
+
+                    #nested_fields
+                };
+            }
+
+            #[doc(hidden)]
+            #[allow(unused_imports)]
+            pub(crate) use nested_fields;
+        })
     }
 }
 
+/// Returns the path to the mod of a nested struct, relative to the mod of its nested fields
+///
+/// Without a given path, the mod is named after the type of the field like the mod of the
+/// nested struct by default.
+fn nested_path(path: Option<Path>, ty: &Type) -> syn::Result<Path> {
+    let path = match (path, ty) {
+        (Some(path), _) => path,
+        (None, Type::Path(type_path)) if type_path.qself.is_none() => {
+            let mut path = type_path.path.clone();
+            let segment = path.segments.last_mut().unwrap();
+            segment.ident = format_ident!("{}", segment.ident.to_string().to_case(Case::Snake));
+            segment.arguments = PathArguments::None;
+            path
+        }
+        (None, ty) => {
+            return Err(syn::Error::new_spanned(
+                ty,
+                "The mod of the nested struct has to be given with `#[event(nested = path)]`",
+            ))
+        }
+    };
+
+    // the nested fields are generated two mods below the struct
+    let absolute = path.leading_colon.is_some()
+        || path
+            .segments
+            .first()
+            .is_some_and(|segment| segment.ident == "crate");
+    match absolute {
+        true => Ok(path),
+        false => Ok(syn::parse_quote!(super::super::#path)),
+    }
+}
+
+/// Parses the generics of a parent given with `#[parent_generics(<...> where ...)]`
+fn parse_generics(input: ParseStream) -> syn::Result<Generics> {
+    let mut generics = input.parse::<Generics>()?;
+    generics.where_clause = input.parse::<Option<WhereClause>>()?;
+    Ok(generics)
+}
+
 struct Field {
     name: Ident,
     attributes: FieldAttributes,
-    event_name: String,
+    event_name: TokenStream,
     get_cmd: Ident,
+    set_cmd: Ident,
+    /// The generics of the `Field` implementation
+    impl_generics: TokenStream,
+    /// The parent including its generics
+    parent_ty: TokenStream,
+    where_clause: Option<WhereClause>,
 }
 
 struct FieldAttributes {
     pub parent: Ident,
-    pub parent_field_name: Option<Ident>,
+    /// The field of the parent, which is a path of members for a nested field
+    pub parent_field_name: Option<Punctuated<Member, Token![.]>>,
     pub parent_field_ty: Type,
+    pub writable: bool,
+    pub instance_key: Option<Member>,
+    pub event_name: Option<TokenStream>,
+    pub no_getter: bool,
+    pub generics: Option<Generics>,
+    /// The mod of the nested fields given with `#[nested(mod)]`, which compose the value of the field
+    pub nested: Option<Ident>,
+    /// The field-struct and member given with `#[nested_in(path, member)]`, which a nested
+    /// field is part of
+    pub nested_in: Option<(Path, Member)>,
+    /// `true` when the changes of the field are emitted as deltas, see `CollectionField`
+    pub collection: bool,
 }
 
 fn get_field_values(attrs: Vec<Attribute>) -> FieldAttributes {
@@ -98,7 +897,10 @@ fn get_field_values(attrs: Vec<Attribute>) -> FieldAttributes {
     let parent_field_name = attrs
         .iter()
         .find(|a| a.path().is_ident("parent_field_name"))
-        .and_then(|name| name.parse_args().ok());
+        .map(|name| {
+            name.parse_args_with(Punctuated::parse_separated_nonempty)
+                .unwrap()
+        });
 
     let parent_field_ty = attrs
         .iter()
@@ -107,23 +909,109 @@ fn get_field_values(attrs: Vec<Attribute>) -> FieldAttributes {
         .parse_args()
         .unwrap();
 
+    let writable = attrs.iter().any(|a| a.path().is_ident("writable"));
+
+    let instance_key = attrs
+        .iter()
+        .find(|a| a.path().is_ident("instance_key"))
+        .map(|key| key.parse_args().unwrap());
+
+    let no_getter = attrs.iter().any(|a| a.path().is_ident("no_getter"));
+
+    let event_name = attrs
+        .iter()
+        .find(|a| a.path().is_ident("event_name"))
+        .map(|name| name.parse_args::<Expr>().unwrap().to_token_stream());
+
+    let generics = attrs
+        .iter()
+        .find(|a| a.path().is_ident("parent_generics"))
+        .map(|generics| generics.parse_args_with(parse_generics).unwrap());
+
+    let nested = attrs
+        .iter()
+        .find(|a| a.path().is_ident("nested"))
+        .map(|nested| nested.parse_args().unwrap());
+
+    let nested_in = attrs
+        .iter()
+        .find(|a| a.path().is_ident("nested_in"))
+        .map(|nested_in| {
+            nested_in
+                .parse_args_with(|input: ParseStream| {
+                    let path = input.parse::<Path>()?;
+                    input.parse::<Token![,]>()?;
+                    Ok((path, input.parse::<Member>()?))
+                })
+                .unwrap()
+        });
+
+    let collection = attrs.iter().any(|a| a.path().is_ident("collection"));
+
     FieldAttributes {
         parent,
         parent_field_name,
         parent_field_ty,
+        writable,
+        instance_key,
+        event_name,
+        no_getter,
+        generics,
+        nested,
+        nested_in,
+        collection,
     }
 }
 
 fn prepare_field(derive_input: DeriveInput) -> Field {
     let name = derive_input.ident.clone();
     let attributes = get_field_values(derive_input.attrs);
-    let event_name = format!("{}::{}", &attributes.parent, &name);
+    // without a given name, the event is named by the path of the field-struct
+    let event_name = attributes.event_name.clone().unwrap_or_else(|| {
+        let name = name.to_string();
+        quote!(concat!(module_path!(), "::", #name))
+    });
     let get_cmd = format_ident!("get_{}_{}", &attributes.parent, name);
+    let set_cmd = format_ident!("set_{}_{}", &attributes.parent, name);
+
+    let parent = &attributes.parent;
+    let (impl_generics, parent_ty, where_clause, event_name) = match &attributes.generics {
+        // the event names of a generic parent depend on its instantiation, see `EventStruct::derive_tags`
+        Some(generics) => {
+            let (impl_generics, ty_generics, _) = generics.split_for_impl();
+            let parent_ty = quote!(#parent #ty_generics);
+            let parent_field_ty = &attributes.parent_field_ty;
+
+            let mut where_generics = generics.clone();
+            let where_clause = where_generics.make_where_clause();
+            where_clause
+                .predicates
+                .push(syn::parse_quote!(#parent_ty: Tag + ::tauri_interop::event::Parent));
+            where_clause.predicates.push(syn::parse_quote! {
+                #parent_field_ty: Clone
+                    + ::tauri_interop::export::serde::Serialize
+                    + ::tauri_interop::export::serde::de::DeserializeOwned
+                    + 'static
+            });
+
+            (
+                impl_generics.to_token_stream(),
+                parent_ty.clone(),
+                where_generics.where_clause,
+                quote!(<#parent_ty as Tag>::#name),
+            )
+        }
+        None => (TokenStream::new(), quote!(#parent), None, event_name),
+    };
 
     Field {
         event_name,
         name,
         attributes,
         get_cmd,
+        set_cmd,
+        impl_generics,
+        parent_ty,
+        where_clause,
     }
 }
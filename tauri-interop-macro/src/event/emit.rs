@@ -1,42 +1,113 @@
 use proc_macro::TokenStream;
 
-use quote::{quote, ToTokens};
-use syn::{parse_macro_input, DeriveInput};
+use quote::{format_ident, quote, ToTokens};
+use syn::{parse_macro_input, parse_quote, DeriveInput, Expr};
 
+use crate::command::collect::event_names_const;
 use crate::event::{EventField, EventStruct, Field, FieldAttributes};
 
 pub fn derive(stream: TokenStream) -> TokenStream {
     let stream_struct = parse_macro_input!(stream as DeriveInput);
+    let event = match super::prepare_event(stream_struct) {
+        Ok(event) => event,
+        Err(why) => return TokenStream::from(why.to_compile_error()),
+    };
+    if event.to_host {
+        return super::host::derive(event);
+    }
+
+    let parent_generics = event.parent_generics_attr();
+    let tags = event.derive_tags(false);
+    let tag_getters = derive_tag_getters(&event);
+    let nested_mods = event.derive_nested_mods();
+    let nested_macro = event.derive_nested_macro(false);
+    let event_names = event.event_names();
+    let mut where_clause = event.generic_where_clause();
+    if cfg!(feature = "initial_value") && !event.generics.params.is_empty() {
+        // the fields of a generic struct require it to be managed, see `ManagedEmit`
+        where_clause
+            .get_or_insert_with(|| syn::parse_quote!(where))
+            .predicates
+            .push(syn::parse_quote!(Self: Send + Sync + 'static));
+    }
+
     let EventStruct {
         name,
         mod_name,
         fields,
-    } = super::prepare_event(stream_struct);
+        all_field,
+        all_event_name,
+        key,
+        generics,
+        ..
+    } = event;
+
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+
+    let instance_key = key.map(|key| quote!(#[instance_key(#key)]));
 
     let emit_fields = fields.iter().map(|field| {
         let EventField {
             field_name,
             parent_field_name,
             parent_field_ty,
+            event_name,
+            options,
+            ..
         } = field;
 
+        let writable = options.writable.then_some(quote!(#[writable]));
+        let no_getter = options.no_getter.then_some(quote!(#[no_getter]));
+        let collection = options.collection.then_some(quote!(#[collection]));
+
         quote! {
             #[allow(dead_code)]
             #[derive(::tauri_interop::EmitField)]
             #[parent(#name)]
             #[parent_field_name(#parent_field_name)]
             #[parent_field_ty(#parent_field_ty)]
+            #[event_name(#event_name)]
+            #writable
+            #no_getter
+            #instance_key
+            #parent_generics
+            #collection
             pub struct #field_name;
         }
     });
 
-    let event_fields = fields.iter().map(|field| &field.field_name);
+    let emit_all_field = all_field.as_ref().map(|all_field| {
+        quote! {
+            #[allow(dead_code)]
+            #[derive(::tauri_interop::EmitField)]
+            #[parent(#name)]
+            #[parent_field_ty(#name #ty_generics)]
+            #[event_name(#all_event_name)]
+            #instance_key
+            #parent_generics
+            pub struct #all_field;
+        }
+    });
+
+    let event_fields = fields
+        .iter()
+        .map(|field| &field.field_name)
+        .chain(all_field.as_ref());
     let commands_attr = cfg!(feature = "initial_value")
         .then_some(quote!(#[::tauri_interop::commands]))
         .unwrap_or_default();
-    let collect_command = cfg!(feature = "initial_value")
-        .then_some(quote!(::tauri_interop::collect_commands!();))
-        .unwrap_or_default();
+    // without getter commands the mod only holds the event names, so that it can still be
+    // checked by `combine_handlers!`
+    let collect_command = if cfg!(feature = "initial_value") {
+        quote!(::tauri_interop::collect_commands!( #( #event_names ),* );)
+    } else {
+        event_names_const(
+            event_names
+                .iter()
+                .map(|event_name| -> Expr { parse_quote!(#event_name) })
+                .collect(),
+        )
+    };
 
     let stream = quote! {
         #commands_attr
@@ -45,10 +116,20 @@ pub fn derive(stream: TokenStream) -> TokenStream {
 
             #( #emit_fields )*
 
+            #emit_all_field
+
+            #tags
+
+            #tag_getters
+
+            #nested_mods
+
+            #nested_macro
+
             #collect_command
         }
 
-        impl ::tauri_interop::event::Emit for #name {
+        impl #impl_generics ::tauri_interop::event::Emit for #name #ty_generics #where_clause {
             fn emit_all(&self, handle: &::tauri_interop::export::tauri::AppHandle) -> Result<(), ::tauri_interop::export::tauri::Error> {
                 use #mod_name::*;
                 use ::tauri_interop::event::Field;
@@ -79,6 +160,54 @@ pub fn derive(stream: TokenStream) -> TokenStream {
     TokenStream::from(stream.to_token_stream())
 }
 
+/// Generates the getter commands of a generic struct for each of its tags
+///
+/// The commands are suffixed by the tag, as a command can't be generic.
+fn derive_tag_getters(event: &EventStruct) -> Option<proc_macro2::TokenStream> {
+    if !cfg!(feature = "initial_value") || event.tags.is_empty() {
+        return None;
+    }
+
+    let name = &event.name;
+    let fields = event
+        .fields
+        .iter()
+        .filter(|field| !field.options.no_getter)
+        .map(|field| {
+            let parent_field_name = &field.parent_field_name;
+            (&field.field_name, quote!(parent.#parent_field_name.clone()))
+        })
+        .chain(
+            event
+                .all_field
+                .iter()
+                .map(|all_field| (all_field, quote!(parent.clone()))),
+        )
+        .collect::<Vec<_>>();
+
+    let getters = event.tags.iter().flat_map(|(ty, tag)| {
+        fields.iter().map(move |(field_name, value)| {
+            let get_cmd = format_ident!("get_{}_{}_{}", name, field_name, tag);
+
+            quote! {
+                #[allow(non_snake_case)]
+                #[tauri_interop::command]
+                pub fn #get_cmd(handle: ::tauri_interop::export::tauri::AppHandle) -> Result<::tauri_interop::event::Revisioned<<#field_name as ::tauri_interop::event::Field<#ty>>::Type>, ::tauri_interop::event::EventError> {
+                    use ::tauri_interop::event::{ManagedEmit, EventError, Revisioned};
+
+                    // the revision is acquired first, so that the value is at least as new as the revision
+                    let revision = ::tauri_interop::event::current_revision();
+                    <#ty>::get_value::<#field_name>(&handle, |parent| #value)
+                        .map(|value| Revisioned { revision, value })
+                        .ok_or(EventError::StateIsNotRegistered(stringify!(#ty).into()))
+                }
+            }
+        })
+    });
+
+    Some(quote!( #( #getters )* ))
+}
+
 pub fn derive_field(stream: TokenStream) -> TokenStream {
     let derive_input = syn::parse_macro_input!(stream as DeriveInput);
 
@@ -87,51 +216,189 @@ pub fn derive_field(stream: TokenStream) -> TokenStream {
         attributes,
         event_name,
         get_cmd,
+        set_cmd,
+        impl_generics,
+        parent_ty,
+        where_clause,
     } = super::prepare_field(derive_input);
 
     let FieldAttributes {
         parent,
         parent_field_name,
         parent_field_ty,
+        writable,
+        instance_key,
+        no_getter,
+        generics,
+        collection,
+        ..
     } = attributes;
 
-    parent_field_name
-        .as_ref()
-        .expect("name attribute was expected");
+    // without the name of a field, the field represents the whole parent
+    let (value, assign, value_mut) = match &parent_field_name {
+        Some(parent_field_name) => (
+            quote!(parent.#parent_field_name.clone()),
+            quote!(parent.#parent_field_name = v),
+            quote!(&mut parent.#parent_field_name),
+        ),
+        None => (quote!(parent.clone()), quote!(*parent = v), quote!(parent)),
+    };
+
+    // the key of an instance is folded into the event name
+    let event = match &instance_key {
+        Some(key) => quote! {
+            ::std::borrow::Cow::Owned(<Self as ::tauri_interop::event::InstanceField<#parent>>::instance_event_name(&parent.#key.to_string()))
+        },
+        None => quote!(::std::borrow::Cow::Borrowed(Self::EVENT_NAME)),
+    };
+
+    let instance_field = instance_key.is_some().then_some(quote! {
+        impl ::tauri_interop::event::InstanceField<#parent> for #name {}
+    });
+
+    // a collection is patched with deltas, so its revision has to match the value exactly
+    let get_value = match &instance_key {
+        Some(_) => quote! {
+            #parent::get_instance_value::<#name>(&handle, &key, |parent| #value)
+        },
+        None if collection => quote! {
+            #parent::get_value::<#name>(&handle, |parent| {
+                revision.set(::tauri_interop::event::current_revision());
+                #value
+            })
+        },
+        None => quote! {
+            #parent::get_value::<#name>(&handle, |parent| #value)
+        },
+    };
+    let revision = match collection {
+        true => quote!(let revision = ::std::cell::Cell::new(0);),
+        false => quote!(let revision = ::tauri_interop::event::current_revision();),
+    };
+    let take_revision = match collection {
+        true => quote!(revision.get()),
+        false => quote!(revision),
+    };
+
+    // the whole value of a collection takes part in the revisions of its deltas
+    let next_revision = match collection {
+        true => quote!(::tauri_interop::event::next_collection_revision(Self::EVENT_NAME).1),
+        false => quote!(::tauri_interop::event::next_revision()),
+    };
+
+    let change_events = collection.then_some(quote! {
+        const CHANGE_EVENTS: &'static [&'static str] = &[#event_name, concat!(#event_name, "::delta")];
+    });
+
+    // the events of an instance are emitted per key, so they can't be listened to beforehand
+    let single_event_field = instance_key.is_none().then_some(quote! {
+        impl #impl_generics ::tauri_interop::event::SingleEventField<#parent_ty> for #name #where_clause {}
+    });
 
-    let get_cmd = cfg!(feature = "initial_value").then_some(quote! {
+    let collection_field = collection.then_some(quote! {
+        impl ::tauri_interop::event::CollectionField<#parent> for #name {}
+    });
+    let key_arg = instance_key.is_some().then_some(quote!(key: String,));
+
+    // the getters of a generic parent are generated per tag, see `derive`
+    let get_cmd = (cfg!(feature = "initial_value") && !no_getter && generics.is_none()).then_some(quote! {
             #[allow(non_snake_case)]
             #[tauri_interop::command]
-            pub fn #get_cmd(handle: ::tauri_interop::export::tauri::AppHandle) -> Result<#parent_field_ty, ::tauri_interop::event::EventError> {
+            pub fn #get_cmd(handle: ::tauri_interop::export::tauri::AppHandle, #key_arg) -> Result<::tauri_interop::event::Revisioned<#parent_field_ty>, ::tauri_interop::event::EventError> {
                 use ::tauri_interop::export::tauri::Manager;
-                use ::tauri_interop::event::{Field, ManagedEmit, EventError};
+                use ::tauri_interop::event::{Field, ManagedEmit, EventError, Revisioned};
 
-                #parent::get_value::<#name>(&handle, |parent| parent.#parent_field_name.clone())
+                // the revision is acquired first (or together with a collection), so that the value is
+                // at least as new as the revision
+                #revision
+                #get_value
+                    .map(|value| Revisioned { revision: #take_revision, value })
                     .ok_or(EventError::StateIsNotRegistered(stringify!(#parent).into()))
             }
         }).unwrap_or_default();
 
+    let set_cmd = writable.then_some(quote! {
+            #[allow(non_snake_case)]
+            #[tauri_interop::command]
+            pub fn #set_cmd(handle: ::tauri_interop::export::tauri::AppHandle, value: #parent_field_ty) -> Result<(), ::tauri_interop::event::EventError> {
+                use ::tauri_interop::event::{Field, ManagedEmit, EventError};
+
+                #parent::update_value(&handle, |parent| <#name as Field<#parent>>::update(parent, &handle, value))
+                    .ok_or(EventError::StateIsNotRegistered(stringify!(#parent).into()))?
+                    .map_err(|why| EventError::EmitFailed(why.to_string()))
+            }
+        }).unwrap_or_default();
+
     let stream = quote! {
-        impl ::tauri_interop::event::Field<#parent> for #name {
+        impl #impl_generics ::tauri_interop::event::Field<#parent_ty> for #name #where_clause {
             type Type = #parent_field_ty;
 
             const EVENT_NAME: &'static str = #event_name;
+            #change_events
 
-            fn emit(parent: &#parent, handle: &::tauri_interop::export::tauri::AppHandle) -> Result<(), ::tauri_interop::export::tauri::Error> {
+            fn revisioned_event(parent: &#parent_ty) -> (::std::borrow::Cow<'static, str>, ::tauri_interop::event::Revisioned<Self::Type>) {
+                let revisioned = ::tauri_interop::event::Revisioned {
+                    revision: #next_revision,
+                    value: #value,
+                };
+
+                (#event, revisioned)
+            }
+
+            fn emit(parent: &#parent_ty, handle: &::tauri_interop::export::tauri::AppHandle) -> Result<(), ::tauri_interop::export::tauri::Error> {
                 use ::tauri_interop::export::tauri::Emitter;
 
-                ::tauri_interop::export::log::trace!("Emitted event [{}]", #event_name);
+                let (event, revisioned) = Self::revisioned_event(parent);
 
-                handle.emit(#event_name, parent.#parent_field_name.clone())
+                ::tauri_interop::export::log::trace!("Emitted event [{}]", event);
+
+                handle.emit(&event, revisioned)
             }
 
-            fn update(parent: &mut #parent, handle: &::tauri_interop::export::tauri::AppHandle, v: Self::Type) -> Result<(), ::tauri_interop::export::tauri::Error> {
-                parent.#parent_field_name = v;
+            fn emit_to(parent: &#parent_ty, handle: &::tauri_interop::export::tauri::AppHandle, target: ::tauri_interop::export::tauri::EventTarget) -> Result<(), ::tauri_interop::export::tauri::Error> {
+                use ::tauri_interop::export::tauri::Emitter;
+
+                let (event, revisioned) = Self::revisioned_event(parent);
+
+                ::tauri_interop::export::log::trace!("Emitted event [{}] to {:?}", event, target);
+
+                handle.emit_to(target, &event, revisioned)
+            }
+
+            fn emit_filter(parent: &#parent_ty, handle: &::tauri_interop::export::tauri::AppHandle, filter: impl Fn(&::tauri_interop::export::tauri::EventTarget) -> bool) -> Result<(), ::tauri_interop::export::tauri::Error> {
+                use ::tauri_interop::export::tauri::Emitter;
+
+                let (event, revisioned) = Self::revisioned_event(parent);
+
+                ::tauri_interop::export::log::trace!("Emitted filtered event [{}]", event);
+
+                handle.emit_filter(&event, revisioned, filter)
+            }
+
+            fn update(parent: &mut #parent_ty, handle: &::tauri_interop::export::tauri::AppHandle, v: Self::Type) -> Result<(), ::tauri_interop::export::tauri::Error> {
+                #assign;
                 Self::emit(parent, handle)
             }
+
+            fn update_to(parent: &mut #parent_ty, handle: &::tauri_interop::export::tauri::AppHandle, target: ::tauri_interop::export::tauri::EventTarget, v: Self::Type) -> Result<(), ::tauri_interop::export::tauri::Error> {
+                #assign;
+                Self::emit_to(parent, handle, target)
+            }
+
+            fn value_mut(parent: &mut #parent_ty) -> &mut Self::Type {
+                #value_mut
+            }
         }
 
+        #instance_field
+
+        #single_event_field
+
+        #collection_field
+
         #get_cmd
+
+        #set_cmd
     };
 
     TokenStream::from(stream.to_token_stream())
@@ -141,9 +408,18 @@ pub fn derive_field(stream: TokenStream) -> TokenStream {
 pub fn derive_managed_emit(stream: TokenStream) -> TokenStream {
     let stream_struct = parse_macro_input!(stream as DeriveInput);
     let struct_ident = stream_struct.ident;
+    let mut generics = stream_struct.generics;
+    if !generics.params.is_empty() {
+        // `Emit` is only implemented for the tagged instantiations of a generic struct
+        generics
+            .make_where_clause()
+            .predicates
+            .push(syn::parse_quote!(Self: ::tauri_interop::event::Emit + Send + Sync + 'static));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     let stream = quote! {
-        impl ::tauri_interop::event::ManagedEmit for #struct_ident {}
+        impl #impl_generics ::tauri_interop::event::ManagedEmit for #struct_ident #ty_generics #where_clause {}
     };
     TokenStream::from(stream.to_token_stream())
 }
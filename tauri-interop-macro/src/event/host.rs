@@ -0,0 +1,86 @@
+use proc_macro::TokenStream;
+
+use quote::{quote, ToTokens};
+
+use crate::event::{EventField, EventStruct};
+
+/// Derives `HostEvent` for a struct marked with `#[event(direction = "to_host")]`
+///
+/// In contrast to [super::emit] and [super::listen] the generated fields are the same for
+/// both architectures, only `emit_all` is solely generated for wasm.
+pub(super) fn derive(event: EventStruct) -> TokenStream {
+    let EventStruct {
+        name,
+        mod_name,
+        fields,
+        all_field,
+        all_event_name,
+        ..
+    } = event;
+
+    let host_fields = fields
+        .iter()
+        .map(
+            |EventField {
+                 field_name,
+                 parent_field_ty,
+                 event_name,
+                 ..
+             }| (field_name, quote!(#parent_field_ty), event_name),
+        )
+        .chain(
+            all_field
+                .as_ref()
+                .map(|all_field| (all_field, quote!(#name), &all_event_name)),
+        )
+        .map(|(field_name, field_ty, event_name)| {
+            quote! {
+                #[allow(dead_code)]
+                pub struct #field_name;
+
+                impl ::tauri_interop::event::HostField<#name> for #field_name {
+                    type Type = #field_ty;
+
+                    const EVENT_NAME: &'static str = #event_name;
+                }
+            }
+        });
+
+    let emit_all = cfg!(feature = "_wasm").then(|| {
+        let emit_fields = fields.iter().map(
+            |EventField {
+                 field_name,
+                 parent_field_name,
+                 ..
+             }| quote!(#mod_name::#field_name::emit(self.#parent_field_name.clone()).await?;),
+        );
+        let emit_all_field = all_field
+            .as_ref()
+            .map(|all_field| quote!(#mod_name::#all_field::emit(self.clone()).await?;));
+
+        quote! {
+            async fn emit_all(&self) -> Result<(), ::tauri_interop::event::HostEmitError> {
+                use ::tauri_interop::event::HostField;
+
+                #( #emit_fields )*
+                #emit_all_field
+
+                Ok(())
+            }
+        }
+    });
+
+    let stream = quote! {
+        pub mod #mod_name {
+            use super::*;
+
+            #( #host_fields )*
+        }
+
+        impl ::tauri_interop::event::HostEvent for #name {
+            #emit_all
+        }
+    };
+
+    TokenStream::from(stream.to_token_stream())
+}
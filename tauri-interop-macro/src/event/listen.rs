@@ -1,47 +1,186 @@
 use proc_macro::TokenStream;
 
-use quote::{quote, ToTokens};
-use syn::{parse_macro_input, DeriveInput};
+use convert_case::{Case, Casing};
+use proc_macro2::Ident;
+use quote::{format_ident, quote, ToTokens};
+use syn::{parse_macro_input, DeriveInput, Fields, Generics, Variant, WhereClause};
 
 use crate::event::{EventField, EventStruct, Field, FieldAttributes};
 
 pub fn derive(stream: TokenStream) -> TokenStream {
     let stream_struct = parse_macro_input!(stream as DeriveInput);
+    let event = match super::prepare_event(stream_struct) {
+        Ok(event) => event,
+        Err(why) => return TokenStream::from(why.to_compile_error()),
+    };
+    if event.to_host {
+        return super::host::derive(event);
+    }
+
+    let parent_generics = event.parent_generics_attr();
+    let tags = event.derive_tags(true);
+    let variant_where_clause = event.generic_where_clause();
+    let nested_mods = event.derive_nested_mods();
+    let nested_macro = event.derive_nested_macro(true);
+
     let EventStruct {
         name,
         mod_name,
         fields,
-    } = super::prepare_event(stream_struct);
+        all_field,
+        all_event_name,
+        key,
+        variants,
+        generics,
+        ..
+    } = event;
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let instance_key = key.map(|key| quote!(#[instance_key(#key)]));
 
     let listen_fields = fields.iter().map(|field| {
         let EventField {
             field_name,
             parent_field_ty,
+            event_name,
+            options,
+            nested,
             ..
         } = field;
 
+        let writable = options.writable.then_some(quote!(#[writable]));
+        let no_getter = options.no_getter.then_some(quote!(#[no_getter]));
+        let nested = nested.as_ref().map(|nested| {
+            let mod_name = &nested.mod_name;
+            quote!(#[nested(#mod_name)])
+        });
+        let collection = options.collection.then_some(quote!(#[collection]));
+
         quote! {
             #[allow(dead_code)]
             #[derive(::tauri_interop::ListenField)]
             #[parent(#name)]
             #[parent_field_ty(#parent_field_ty)]
+            #[event_name(#event_name)]
+            #writable
+            #no_getter
+            #instance_key
+            #parent_generics
+            #nested
+            #collection
             pub struct #field_name;
         }
     });
 
+    let listen_all_field = all_field.as_ref().map(|all_field| {
+        quote! {
+            #[allow(dead_code)]
+            #[derive(::tauri_interop::ListenField)]
+            #[parent(#name)]
+            #[parent_field_ty(#name #ty_generics)]
+            #[event_name(#all_event_name)]
+            #instance_key
+            #parent_generics
+            pub struct #all_field;
+        }
+    });
+
+    let listen_variants = all_field.as_ref().map(|all_field| {
+        derive_variant_listeners(
+            &name,
+            &mod_name,
+            all_field,
+            &variants,
+            &generics,
+            variant_where_clause.as_ref(),
+        )
+    });
+
     let stream = quote! {
         pub mod #mod_name {
             use super::*;
 
             #( #listen_fields )*
+
+            #listen_all_field
+
+            #tags
+
+            #nested_mods
+
+            #nested_macro
         }
 
-        impl ::tauri_interop::event::Listen for #name {}
+        impl #impl_generics ::tauri_interop::event::Listen for #name #ty_generics #where_clause {}
+
+        #listen_variants
     };
 
     TokenStream::from(stream.to_token_stream())
 }
 
+/// Generates a `listen_to_{variant}` function for each variant of an enum
+///
+/// The callback receives the fields of the variant, whenever the enum is emitted as that variant.
+fn derive_variant_listeners(
+    name: &Ident,
+    mod_name: &Ident,
+    all_field: &Ident,
+    variants: &[Variant],
+    generics: &Generics,
+    where_clause: Option<&WhereClause>,
+) -> Option<proc_macro2::TokenStream> {
+    if variants.is_empty() {
+        return None;
+    }
+
+    let listeners = variants.iter().map(|variant| {
+        let variant_name = &variant.ident;
+        let listen_fn = format_ident!("listen_to_{}", variant_name.to_string().to_case(Case::Snake));
+        let bindings = (0..variant.fields.len())
+            .map(|index| format_ident!("__{index}"))
+            .collect::<Vec<_>>();
+        let types = variant.fields.iter().map(|field| &field.ty);
+        let pattern = match &variant.fields {
+            Fields::Named(fields) => {
+                let idents = fields.named.iter().map(|field| &field.ident);
+                quote!(Self::#variant_name { #( #idents: #bindings ),* })
+            }
+            Fields::Unnamed(_) => quote!(Self::#variant_name( #( #bindings ),* )),
+            Fields::Unit => quote!(Self::#variant_name),
+        };
+        let doc = format!(" Registers a callback, which receives the fields of [Self::{variant_name}] whenever it is emitted");
+
+        quote! {
+            #[doc = #doc]
+            pub fn #listen_fn(
+                callback: impl Fn( #( #types ),* ) + 'static,
+            ) -> impl ::std::future::Future<Output = ::tauri_interop::event::ListenResult> {
+                use ::tauri_interop::event::Field;
+
+                ::tauri_interop::event::ListenHandle::subscribe(
+                    <#mod_name::#all_field as Field<Self>>::EVENT_NAME,
+                    move |update: ::tauri_interop::event::Revisioned<Self>| {
+                        #[allow(irrefutable_let_patterns)]
+                        if let #pattern = update.value {
+                            callback( #( #bindings ),* )
+                        }
+                    },
+                )
+            }
+        }
+    });
+
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+
+    Some(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #( #listeners )*
+        }
+    })
+}
+
 pub fn derive_field(stream: TokenStream) -> TokenStream {
     let derive_input = syn::parse_macro_input!(stream as DeriveInput);
 
@@ -50,39 +189,188 @@ pub fn derive_field(stream: TokenStream) -> TokenStream {
         attributes,
         event_name,
         get_cmd,
+        set_cmd,
+        impl_generics,
+        parent_ty,
+        where_clause,
     } = super::prepare_field(derive_input);
 
     let FieldAttributes {
         parent,
         parent_field_ty,
+        writable,
+        instance_key,
+        no_getter,
+        generics,
+        nested,
+        nested_in,
+        collection,
         ..
     } = attributes;
 
-    let get_cmd_fn = cfg!(feature = "initial_value")
+    let key_arg = instance_key.is_some().then_some(quote!(key: String));
+    let tag_getter = format_ident!("get_{name}");
+
+    // the value is wrapped in an option, so that the type of the field doesn't need to implement `Default`
+    let get_cmd_fn = (cfg!(feature = "initial_value")
+        && !no_getter
+        && generics.is_none()
+        && nested_in.is_none())
         .then_some(quote! {
             #[allow(non_snake_case)]
             #[tauri_interop::command]
-            pub fn #get_cmd() -> Result<#parent_field_ty, ::tauri_interop::event::EventError> {}
+            pub fn #get_cmd(#key_arg) -> Result<Option<::tauri_interop::event::Revisioned<#parent_field_ty>>, ::tauri_interop::event::EventError> {}
         })
         .unwrap_or_default();
 
-    let get_value = cfg!(feature = "initial_value")
+    // the value of an instance is only available with its key, see `InstanceField`
+    let get_value = match instance_key.is_some() || no_getter {
+        _ if !cfg!(feature = "initial_value") => None,
+        // the value of a nested field is part of the value of the field it is nested in
+        _ if nested_in.is_some() => nested_in.as_ref().map(|(nested_in, member)| quote! {
+            async fn get_revisioned_value() -> Result<::tauri_interop::event::Revisioned<Self::Type>, ::tauri_interop::event::EventError> {
+                <#nested_in as ::tauri_interop::event::Field<#parent_ty>>::get_revisioned_value()
+                    .await
+                    .map(|revisioned| ::tauri_interop::event::Revisioned {
+                        revision: revisioned.revision,
+                        value: revisioned.value.#member,
+                    })
+            }
+        }),
+        true => Some(quote! {
+            async fn get_revisioned_value() -> Result<::tauri_interop::event::Revisioned<Self::Type>, ::tauri_interop::event::EventError> {
+                Err(::tauri_interop::event::EventError::ValueUnavailable(Self::EVENT_NAME.into()))
+            }
+        }),
+        // the getter of a generic parent is provided by the tag of the instantiation
+        false if generics.is_some() => Some(quote! {
+            async fn get_revisioned_value() -> Result<::tauri_interop::event::Revisioned<Self::Type>, ::tauri_interop::event::EventError> {
+                <#parent_ty as Tag>::#tag_getter().await?.ok_or_else(|| {
+                    ::tauri_interop::event::EventError::ValueUnavailable(Self::EVENT_NAME.into())
+                })
+            }
+        }),
+        false => Some(quote! {
+            async fn get_revisioned_value() -> Result<::tauri_interop::event::Revisioned<Self::Type>, ::tauri_interop::event::EventError> {
+                #get_cmd().await?.ok_or_else(|| {
+                    ::tauri_interop::event::EventError::ValueUnavailable(Self::EVENT_NAME.into())
+                })
+            }
+        }),
+    };
+
+    let get_instance_value = cfg!(feature = "initial_value").then_some(match no_getter {
+        true => quote! {
+            async fn get_instance_value(key: &str) -> Result<::tauri_interop::event::Revisioned<Self::Type>, ::tauri_interop::event::EventError> {
+                Err(::tauri_interop::event::EventError::ValueUnavailable(Self::instance_event_name(key)))
+            }
+        },
+        false => quote! {
+            async fn get_instance_value(key: &str) -> Result<::tauri_interop::event::Revisioned<Self::Type>, ::tauri_interop::event::EventError> {
+                #get_cmd(key.to_string()).await?.ok_or_else(|| {
+                    ::tauri_interop::event::EventError::ValueUnavailable(Self::instance_event_name(key))
+                })
+            }
+        },
+    });
+
+    let instance_field = instance_key.is_some().then_some(quote! {
+        impl ::tauri_interop::event::InstanceField<#parent> for #name {
+            #get_instance_value
+        }
+    });
+
+    let set_cmd_fn = writable
         .then_some(quote! {
-            async fn get_value() -> Result<Self::Type, ::tauri_interop::event::EventError> {
-                #get_cmd().await
+            #[allow(non_snake_case)]
+            #[tauri_interop::command]
+            pub fn #set_cmd(value: #parent_field_ty) -> Result<(), ::tauri_interop::event::EventError> {}
+        })
+        .unwrap_or_default();
+
+    let set_value = writable
+        .then_some(quote! {
+            async fn set_value(value: Self::Type) -> Result<(), ::tauri_interop::event::EventError> {
+                #set_cmd(value).await
             }
         })
         .unwrap_or_default();
 
+    // requests the value, when the subscriber missed an event
+    let request_value = cfg!(feature = "initial_value").then_some(quote! {
+        || ::std::boxed::Box::pin(<Self as ::tauri_interop::event::Field<#parent_ty>>::get_revisioned_value()),
+    });
+
+    // the value of the field is composed of the events of its nested fields
+    let subscribe_nested = nested.map(|nested| {
+        quote! {
+            fn subscribe(
+                target: ::tauri_interop::event::ListenTarget,
+                callback: impl Fn(::tauri_interop::event::Revisioned<Self::Type>) + 'static,
+            ) -> impl ::std::future::Future<Output = ::tauri_interop::event::ListenResult> {
+                ::tauri_interop::event::ListenHandle::subscribe_nested(
+                    Self::EVENT_NAME,
+                    target,
+                    #nested::nested_fields(),
+                    #request_value
+                    callback,
+                )
+            }
+        }
+    });
+
+    // the value of a collection is patched by the deltas of the field
+    let subscribe_collection = collection.then_some(quote! {
+        fn subscribe(
+            target: ::tauri_interop::event::ListenTarget,
+            callback: impl Fn(::tauri_interop::event::Revisioned<Self::Type>) + 'static,
+        ) -> impl ::std::future::Future<Output = ::tauri_interop::event::ListenResult> {
+            ::tauri_interop::event::ListenHandle::subscribe_collection(
+                Self::EVENT_NAME,
+                target,
+                #request_value
+                callback,
+            )
+        }
+    });
+
+    let change_events = collection.then_some(quote! {
+        const CHANGE_EVENTS: &'static [&'static str] = &[#event_name, concat!(#event_name, "::delta")];
+    });
+
+    // the events of an instance are emitted per key, so they can't be listened to beforehand
+    let single_event_field = instance_key.is_none().then_some(quote! {
+        impl #impl_generics ::tauri_interop::event::SingleEventField<#parent_ty> for #name #where_clause {}
+    });
+
+    let collection_field = collection.then_some(quote! {
+        impl ::tauri_interop::event::CollectionField<#parent> for #name {}
+    });
+
     let stream = quote! {
         #get_cmd_fn
 
-        impl ::tauri_interop::event::Field<#parent> for #name {
+        #set_cmd_fn
+
+        impl #impl_generics ::tauri_interop::event::Field<#parent_ty> for #name #where_clause {
             type Type = #parent_field_ty;
             const EVENT_NAME: &'static str = #event_name;
+            #change_events
 
             #get_value
+
+            #set_value
+
+            #subscribe_nested
+
+            #subscribe_collection
         }
+
+        #instance_field
+
+        #single_event_field
+
+        #collection_field
     };
 
     TokenStream::from(stream.to_token_stream())
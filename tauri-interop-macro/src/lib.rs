@@ -12,9 +12,11 @@ use std::sync::Mutex;
 use proc_macro_error::{emit_call_site_error, emit_call_site_warning, proc_macro_error};
 use quote::{format_ident, quote, ToTokens};
 use syn::{
-    parse::Parser, parse_macro_input, punctuated::Punctuated, ExprPath, ItemFn, ItemMod, Token,
+    parse::Parser, parse_macro_input, punctuated::Punctuated, Expr, ExprPath, ItemFn, ItemMod,
+    Token,
 };
 
+use crate::command::attributes::CommandAttributes;
 use crate::command::collect::commands_to_punctuated;
 
 mod command;
@@ -36,8 +38,29 @@ mod event;
 /// the mod to any given name.
 ///
 /// The generated field-structs represent a field of the struct and are used for the
-/// derived trait functions. The fields are used to `emit`, `update` or `listen_to` a
-/// given field. For detail usages see the individual traits defined in `tauri-interop`.
+/// derived trait functions. The fields are used to `emit`, `update`, `modify` or `listen_to` a
+/// given field, or to change multiple fields in a `transaction`. For detail usages see the
+/// individual traits defined in `tauri-interop`. The fields of tuple structs are named `F0`,
+/// `F1`, ..., enums are emitted as a whole using the field-struct `All` and get a
+/// `listen_to_{variant}` function in wasm.
+///
+/// The events are named after the path of the field-struct (for example
+/// `my_crate::model::test::FFoo`). Previously they were named `{Struct}::{Field}`, which is kept
+/// with `#[event(prefix = "")]`. Duplicate names are detected by [combine_handlers!].
+///
+/// The generated code is adjusted with `#[event(...)]`:
+/// - on the struct: `prefix = "..."` or `name = "..."` to name the events, `key = field` for
+///   multiple instances (see `InstanceField`), `direction = "to_host"` to derive `HostEvent`
+///   instead, `tag(Paged<User> = "user")` to name the instantiations of a generic struct and
+///   `nestable` to allow nesting the struct
+/// - on a field: `skip`, `rename = "..."`, `name = "..."`, `no_getter`, `writable` (feature
+///   `initial_value`, generates a setter command), `nested` to generate the fields of a nested
+///   `Event` struct in a mod named after the field and `collection` to emit the changes of a
+///   `Vec`, `HashMap` or `BTreeMap` as deltas (see `CollectionField`)
+///
+/// With `#[all_field]` an additional field-struct named `All` (or the name given with
+/// `#[all_field(...)]`) represents the whole struct, which has to implement `Clone`, `Serialize`
+/// and `Deserialize`.
 ///
 /// ### Example
 ///
@@ -65,7 +88,7 @@ mod event;
 /// ```
 #[cfg(feature = "event")]
 #[doc(cfg(feature = "event"))]
-#[proc_macro_derive(Event, attributes(auto_naming, mod_name))]
+#[proc_macro_derive(Event, attributes(auto_naming, mod_name, all_field, event))]
 pub fn derive_event(stream: TokenStream) -> TokenStream {
     if cfg!(feature = "_wasm") {
         event::listen::derive(stream)
@@ -106,7 +129,7 @@ pub fn derive_managed_emit(stream: TokenStream) -> TokenStream {
 /// See [Event] for the usage.
 #[cfg(feature = "event")]
 #[doc(cfg(feature = "event"))]
-#[proc_macro_derive(Emit, attributes(auto_naming, mod_name))]
+#[proc_macro_derive(Emit, attributes(auto_naming, mod_name, all_field, event))]
 pub fn derive_emit(stream: TokenStream) -> TokenStream {
     event::emit::derive(stream)
 }
@@ -116,7 +139,20 @@ pub fn derive_emit(stream: TokenStream) -> TokenStream {
 /// Used for host code generation. It is not intended to be used directly.
 #[cfg(feature = "event")]
 #[doc(cfg(feature = "event"))]
-#[proc_macro_derive(EmitField, attributes(parent, parent_field_name, parent_field_ty))]
+#[proc_macro_derive(
+    EmitField,
+    attributes(
+        parent,
+        parent_field_name,
+        parent_field_ty,
+        event_name,
+        writable,
+        no_getter,
+        instance_key,
+        parent_generics,
+        collection
+    )
+)]
 pub fn derive_emit_field(stream: TokenStream) -> TokenStream {
     event::emit::derive_field(stream)
 }
@@ -127,7 +163,7 @@ pub fn derive_emit_field(stream: TokenStream) -> TokenStream {
 /// See [Event] for the usage.
 #[cfg(feature = "event")]
 #[doc(cfg(feature = "event"))]
-#[proc_macro_derive(Listen, attributes(auto_naming, mod_name))]
+#[proc_macro_derive(Listen, attributes(auto_naming, mod_name, all_field, event))]
 pub fn derive_listen(stream: TokenStream) -> TokenStream {
     event::listen::derive(stream)
 }
@@ -137,15 +173,31 @@ pub fn derive_listen(stream: TokenStream) -> TokenStream {
 /// Used for wasm code generation. It is not intended to be used directly.
 #[cfg(feature = "event")]
 #[doc(cfg(feature = "event"))]
-#[proc_macro_derive(ListenField, attributes(parent, parent_field_ty))]
+#[proc_macro_derive(
+    ListenField,
+    attributes(
+        parent,
+        parent_field_ty,
+        event_name,
+        writable,
+        no_getter,
+        instance_key,
+        parent_generics,
+        nested,
+        nested_in,
+        collection
+    )
+)]
 pub fn derive_listen_field(stream: TokenStream) -> TokenStream {
     event::listen::derive_field(stream)
 }
 
 /// Generates the wasm counterpart to a defined `tauri::command`
+#[proc_macro_error]
 #[proc_macro_attribute]
-pub fn binding(_attributes: TokenStream, stream: TokenStream) -> TokenStream {
-    command::convert_to_binding(stream)
+pub fn binding(attributes: TokenStream, stream: TokenStream) -> TokenStream {
+    let attributes = CommandAttributes::parse(attributes.into());
+    command::convert_to_binding(attributes, stream)
 }
 
 lazy_static::lazy_static! {
@@ -166,7 +218,9 @@ static COMMAND_MOD_NAME: Mutex<Option<String>> = Mutex::new(None);
 /// ### Collecting commands
 /// When this macro is compiled to the host target, additionally to adding the
 /// `tauri::command` macro, the option to auto collect the command via
-/// [macro@collect_commands] and [macro@combine_handlers] is provided.
+/// [macro@collect_commands] and [macro@combine_handlers] is provided. The handlers
+/// generated by these also execute the collected commands, when they are invoked as part
+/// of a `batch`.
 ///
 /// ### Binding generation
 /// All parameter arguments with `tauri` in their name (case-insensitive) are
@@ -179,6 +233,94 @@ static COMMAND_MOD_NAME: Mutex<Option<String>> = Mutex::new(None);
 /// can't be successfully interpreted as a result and by that will result in
 /// wrong type/error handling/serialization.
 ///
+/// ### Cancellation
+/// A command can take a `TauriCancellationToken` argument to get notified when the
+/// frontend isn't interested in the result anymore. The argument is removed from the
+/// generated binding, instead an invocation id is sent. When the future of the binding
+/// is dropped before the command completed, the host is notified via the additionally
+/// generated `cancel_{command}` command, which cancels the token of the invocation.
+///
+/// ```rust
+/// use tauri_interop::command::TauriCancellationToken;
+///
+/// #[tauri_interop_macro::command]
+/// async fn long_export(token: TauriCancellationToken) -> Result<(), String> {
+///     token.cancelled().await;
+///     Err("the frontend dropped the invocation".into())
+/// }
+/// ```
+///
+/// ### Jobs
+/// With `#[tauri_interop::command(job)]` an async command is turned into a job. Invoking
+/// the command spawns the job on the host and returns its id immediately. The job can take
+/// a `TauriJob<P>` argument to report its progress of type `P` and to check whether it was
+/// cancelled. Additionally, the commands `{command}_status`, `{command}_cancel`,
+/// `{command}_result` and `{command}_discard` are generated on the host to control the job.
+///
+/// The generated binding returns a `JobHandle<P, T>` (where `T` is the return type of the
+/// command), which provides the progress as stream, can cancel the job and can be awaited
+/// to retrieve the result. The arguments of a job command have to be owned, because the
+/// job outlives the invocation. Dropping the handle without retrieving the result discards
+/// the job on the host, finished jobs that weren't discarded are evicted after a while.
+///
+/// ```rust
+/// use tauri_interop::command::TauriJob;
+///
+/// #[tauri_interop_macro::command(job)]
+/// async fn import(files: Vec<String>, job: TauriJob<usize>) -> Result<usize, String> {
+///     for (imported, _file) in files.iter().enumerate() {
+///         job.progress(imported);
+///     }
+///
+///     Ok(files.len())
+/// }
+/// ```
+///
+/// ### Caching
+/// Pure reads can be marked with `#[tauri_interop::command(idempotent)]`, so that identical
+/// invocations (same arguments) in the frontend share a pending request. With
+/// `#[tauri_interop::command(cache(ttl = 30))]` successful results are additionally kept for
+/// the given amount of seconds, with `cache` alone until they are invalidated. Additionally,
+/// the binding `invalidate_{command}` is generated, which discards the cached results of the
+/// command. On the host the attributes have no effect.
+///
+/// With `invalidate_on(..)` (feature: `event`) the command declares, that its results depend
+/// on the listed fields of an [Event], e.g. `invalidate_on(State => state::FField)`. The parent
+/// is listed with each field, as the field-struct can be a field of multiple parents. Every
+/// time the event of one of the fields is emitted, the results of the command are invalidated,
+/// like calling `invalidate_{command}`. Only fields implementing `SingleEventField` can be
+/// listed, the fields of a struct with instance keys (`#[event(key = field)]`) emit their events
+/// per key.
+///
+/// ```rust
+/// #[tauri_interop_macro::command(cache(ttl = 30))]
+/// fn list_profiles() -> Vec<String> {
+///     vec![String::from("default")]
+/// }
+/// ```
+///
+/// ### Leptos and Dioxus
+/// With `#[tauri_interop::command(leptos)]` (feature: `leptos`) the helpers
+/// `use_{command}_resource` and `{command}_action` are generated additionally to the binding.
+/// The resource takes a closure returning the arguments as tuple, and is refetched when the
+/// signals read in the closure change or the results of the command are invalidated. The
+/// action takes the arguments as tuple on dispatch. Referenced arguments (e.g. `&str`) are
+/// replaced by their owned type and the arguments have to implement [Clone].
+///
+/// ```ignore
+/// #[component]
+/// fn Profiles() -> impl IntoView {
+///     let (filter, set_filter) = signal(String::new());
+///     let profiles = api::cmd::use_list_profiles_resource(move || (filter.get(),));
+///     let delete = api::cmd::delete_profile_action();
+///
+///     view! { <p>{move || profiles.get().map(|profiles| profiles.join(", "))}</p> }
+/// }
+/// ```
+///
+/// With `#[tauri_interop::command(dioxus)]` (feature: `dioxus`) the hook `use_{command}_resource`
+/// is generated instead, which returns a dioxus `Resource` and takes the arguments the same way.
+///
 /// ### Example - Definition
 ///
 /// ```rust
@@ -220,8 +362,11 @@ static COMMAND_MOD_NAME: Mutex<Option<String>> = Mutex::new(None);
 ///     });
 /// }
 /// ```
+#[proc_macro_error]
 #[proc_macro_attribute]
-pub fn command(_attributes: TokenStream, stream: TokenStream) -> TokenStream {
+pub fn command(attributes: TokenStream, stream: TokenStream) -> TokenStream {
+    let raw_attributes = proc_macro2::TokenStream::from(attributes);
+    let command_attributes = CommandAttributes::parse(raw_attributes.clone());
     let fn_item = parse_macro_input!(stream as ItemFn);
 
     COMMAND_LIST
@@ -229,12 +374,32 @@ pub fn command(_attributes: TokenStream, stream: TokenStream) -> TokenStream {
         .unwrap()
         .insert(fn_item.sig.ident.to_string());
 
+    if command_attributes.job {
+        let host_commands = command::job::host_commands(fn_item.clone());
+        let command_macro = quote! {
+            #[cfg(target_family = "wasm")]
+            #[::tauri_interop::binding(#raw_attributes)]
+            #fn_item
+
+            #host_commands
+        };
+
+        return TokenStream::from(command_macro.to_token_stream());
+    }
+
+    let cancel_command = command::cancel_command(&fn_item);
+    let batch_dispatcher = command::batch::dispatcher(&fn_item);
+
     // root = "tauri_interop", we can only provide an Ident and no path to the re-exported crate
     // see https://github.com/tauri-apps/tauri/blob/dev/crates/tauri-macros/src/command/wrapper.rs#L76
     let command_macro = quote! {
-        #[cfg_attr(target_family = "wasm", ::tauri_interop::binding)]
+        #[cfg_attr(target_family = "wasm", ::tauri_interop::binding(#raw_attributes))]
         #[cfg_attr(not(target_family = "wasm"), ::tauri_interop::export::tauri::command(root = "tauri_interop", rename_all = "snake_case"))]
         #fn_item
+
+        #batch_dispatcher
+
+        #cancel_command
     };
 
     TokenStream::from(command_macro.to_token_stream())
@@ -278,14 +443,24 @@ pub fn commands(_attributes: TokenStream, stream: TokenStream) -> TokenStream {
 /// }
 /// ```
 #[proc_macro]
-pub fn collect_commands(_: TokenStream) -> TokenStream {
+pub fn collect_commands(stream: TokenStream) -> TokenStream {
+    // the event names of the mod are given by the `Event` derive
+    let event_names =
+        parse_macro_input!(stream with Punctuated::<Expr, Token![,]>::parse_terminated);
+
     let mut commands = COMMAND_LIST.lock().unwrap();
-    let stream = command::collect::get_handler_function(
+    let handler_function = command::collect::get_handler_function(
         format_ident!("get_handlers"),
         &commands,
         commands_to_punctuated(&commands),
         Vec::new(),
     );
+    let event_names = command::collect::event_names_const(event_names);
+    let stream = quote! {
+        #handler_function
+
+        #event_names
+    };
 
     // logic for renaming the commands, so that combine methode can just use the provided commands
     if let Some(mod_name) = COMMAND_MOD_NAME.lock().unwrap().as_ref() {
@@ -318,6 +493,10 @@ pub fn collect_commands(_: TokenStream) -> TokenStream {
 /// still require the invocation of [collect_commands!] at the end of a command mod. In
 /// addition, a mod has to be marked with [macro@commands].
 ///
+/// With feature `event`, the evaluation of a const fails when an event name is used by
+/// multiple fields of the combined mods. The mods of `Event` structs always hold their event
+/// names, the event names of other mods are collected by [collect_commands!].
+///
 /// ### Example
 ///
 /// ```
@@ -388,12 +567,20 @@ pub fn combine_handlers(stream: TokenStream) -> TokenStream {
         );
     }
 
-    TokenStream::from(command::collect::get_handler_function(
+    let event_names_check =
+        cfg!(feature = "event").then(|| command::collect::event_names_check(&command_mods));
+    let handler_function = command::collect::get_handler_function(
         format_ident!("get_all_handlers"),
         &commands,
         commands_to_punctuated(&commands),
         command_mods,
-    ))
+    );
+
+    TokenStream::from(quote! {
+        #handler_function
+
+        #event_names_check
+    })
 }
 
 /// Simple macro to include multiple imports (seperated by `|`) not in wasm
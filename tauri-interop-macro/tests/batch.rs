@@ -0,0 +1,197 @@
+//! Every collected command can be executed as part of a batch
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tauri::ipc::{CallbackFn, Invoke, InvokeBody};
+use tauri::test::{get_ipc_response, mock_builder, mock_context, noop_assets, MockRuntime};
+use tauri::webview::InvokeRequest;
+use tauri::{Manager, State, WebviewWindow, WebviewWindowBuilder};
+use tauri_interop::command::batch::{with_batching, ResultKind, ValueKind, BATCH_COMMAND};
+use tauri_interop::command::{cancel_invocation, TauriCancellationToken, TauriState, TauriWebview};
+
+#[tauri_interop::command]
+fn greet(name: &str, times: Option<usize>) -> String {
+    format!("Hello, {name}!").repeat(times.unwrap_or(1))
+}
+
+#[tauri_interop::command]
+fn count(counter: TauriState<AtomicUsize>) -> usize {
+    counter.fetch_add(1, Ordering::SeqCst) + 1
+}
+
+#[tauri_interop::command]
+fn trigger(_state: State<RwLock<String>>, _webview: TauriWebview) {}
+
+#[tauri_interop::command]
+async fn fallible(fail: bool) -> Result<u32, String> {
+    if fail {
+        Err("failed".into())
+    } else {
+        Ok(7)
+    }
+}
+
+#[tauri_interop::command]
+async fn cancellable(token: TauriCancellationToken) -> bool {
+    token.cancelled().await;
+    token.is_cancelled()
+}
+
+tauri_interop::collect_commands!();
+
+// the handler generated by `collect_commands` for the mock runtime, the commands taking a
+// webview are bound to the default runtime
+fn mock_handlers() -> impl Fn(Invoke<MockRuntime>) -> bool + Send + Sync + 'static {
+    with_batching(
+        tauri::generate_handler![greet, count, fallible, cancellable],
+        |message, command, args| match command {
+            "greet" => Some(__batch_greet!(greet, message, args)),
+            "count" => Some(__batch_count!(count, message, args)),
+            "fallible" => Some(__batch_fallible!(fallible, message, args)),
+            "cancellable" => Some(__batch_cancellable!(cancellable, message, args)),
+            _ => None,
+        },
+    )
+}
+
+fn mock_webview() -> WebviewWindow<MockRuntime> {
+    let app = mock_builder()
+        .invoke_handler(mock_handlers())
+        .build(mock_context(noop_assets()))
+        .expect("the app was built");
+    app.manage(AtomicUsize::new(0));
+
+    WebviewWindowBuilder::new(&app, "main", Default::default())
+        .build()
+        .expect("the webview was built")
+}
+
+fn invoke_batch(webview: &WebviewWindow<MockRuntime>, calls: Value) -> Result<Value, Value> {
+    let request = InvokeRequest {
+        cmd: BATCH_COMMAND.into(),
+        callback: CallbackFn(0),
+        error: CallbackFn(1),
+        url: "tauri://localhost".parse().unwrap(),
+        body: InvokeBody::Json(json!({ "calls": calls })),
+        headers: Default::default(),
+        invoke_key: tauri::test::INVOKE_KEY.into(),
+    };
+
+    get_ipc_response(webview, request).map(|body| body.deserialize().expect("json responses"))
+}
+
+// the same conversion as in the generated dispatchers
+macro_rules! response {
+    ($value:expr) => {{
+        let value = $value;
+        (&value).batch_kind().response(value)
+    }};
+}
+
+#[test]
+fn dispatches_collected_commands() {
+    let _ = get_handlers();
+    let webview = mock_webview();
+
+    let responses = invoke_batch(
+        &webview,
+        json!([
+            { "command": "greet", "args": { "name": "batch", "times": 2 } },
+            { "command": "count", "args": {} },
+            { "command": "fallible", "args": { "fail": false } },
+            { "command": "count", "args": {} },
+        ]),
+    );
+
+    assert_eq!(
+        responses,
+        Ok(json!([
+            { "Ok": "Hello, batch!Hello, batch!" },
+            { "Ok": 1 },
+            { "Ok": 7 },
+            { "Ok": 2 },
+        ]))
+    );
+}
+
+#[test]
+fn rejects_failed_calls_separately() {
+    let webview = mock_webview();
+
+    let responses = invoke_batch(
+        &webview,
+        json!([
+            { "command": "fallible", "args": { "fail": true } },
+            { "command": "greet", "args": { "times": 2 } },
+            { "command": "unknown", "args": {} },
+            { "command": BATCH_COMMAND, "args": { "calls": [] } },
+            { "command": "greet", "args": { "name": "batch" } },
+        ]),
+    )
+    .expect("the batch was dispatched");
+
+    assert_eq!(responses[0], json!({ "Err": "failed" }));
+    assert!(responses[1]["Err"]
+        .as_str()
+        .is_some_and(|why| why.contains("greet") && why.contains("name")));
+    assert_eq!(responses[2], json!({ "Err": "command unknown not found" }));
+    assert_eq!(responses[3], json!({ "Err": "batches can't be nested" }));
+    assert_eq!(responses[4], json!({ "Ok": "Hello, batch!" }));
+}
+
+#[test]
+fn rejects_invalid_batches() {
+    let webview = mock_webview();
+
+    let response = invoke_batch(&webview, json!({ "command": "greet" }));
+    assert!(response.is_err_and(|why| why
+        .as_str()
+        .is_some_and(|why| why.contains("invalid batch"))));
+}
+
+#[test]
+fn cancels_batched_commands() {
+    let webview = mock_webview();
+
+    // the token is registered by the invocation id, as soon as the batch is dispatched
+    let completed = Arc::new(AtomicBool::new(false));
+    let canceller = thread::spawn({
+        let completed = completed.clone();
+        move || {
+            while !completed.load(Ordering::SeqCst) {
+                cancel_invocation("main", 7);
+                thread::sleep(Duration::from_millis(5));
+            }
+        }
+    });
+
+    let responses = invoke_batch(
+        &webview,
+        json!([
+            { "command": "cancellable", "args": { "token": 7 } },
+            { "command": "cancellable", "args": {} },
+        ]),
+    );
+    completed.store(true, Ordering::SeqCst);
+    canceller.join().expect("the canceller completed");
+
+    let responses = responses.expect("the batch was dispatched");
+    assert_eq!(responses[0], json!({ "Ok": true }));
+    assert!(responses[1]["Err"]
+        .as_str()
+        .is_some_and(|why| why.contains("cancellable") && why.contains("token")));
+}
+
+#[test]
+fn converts_returned_values() {
+    assert_eq!(response!(()), Ok(Value::Null));
+    assert_eq!(
+        response!(greet("batch", Some(2))),
+        Ok(json!("Hello, batch!Hello, batch!"))
+    );
+    assert_eq!(response!(Ok::<_, String>(7)), Ok(json!(7)));
+    assert_eq!(response!(Err::<u32, _>("failed")), Err(json!("failed")));
+}
@@ -0,0 +1,96 @@
+//! The getter and setter of a writable field acquire the same managed state
+// the host commands are used, `_wasm` would generate the bindings instead
+#![cfg(not(feature = "_wasm"))]
+
+use std::sync::{Mutex, RwLock};
+
+use tauri::test::{mock_app, MockRuntime};
+use tauri::{App, Manager};
+use tauri_interop::event::{state_helper, Field, ManagedEmit, StateAccess};
+use tauri_interop::{Event, ManagedEmit};
+
+#[derive(Event, ManagedEmit)]
+pub struct Player {
+    #[event(writable)]
+    volume: u32,
+}
+
+#[derive(Event)]
+pub struct Locked {
+    #[event(writable)]
+    volume: u32,
+}
+
+impl ManagedEmit for Locked {
+    const STATE_ACCESS: StateAccess = StateAccess::Mutex;
+}
+
+// the same acquisition as the default getter and setter of `ManagedEmit`
+fn get_volume(app: &App<MockRuntime>) -> Option<u32> {
+    state_helper::acquire::<Player, player::FVolume>(app.handle(), Player::STATE_ACCESS, |player| {
+        player.volume
+    })
+}
+
+fn set_volume(app: &App<MockRuntime>, volume: u32) -> Option<()> {
+    state_helper::acquire_mut(app.handle(), Player::STATE_ACCESS, |player: &mut Player| {
+        *player::FVolume::value_mut(player) = volume
+    })
+}
+
+#[test]
+fn round_trips_a_state_in_an_rwlock() {
+    let app = mock_app();
+    app.manage(RwLock::new(Player { volume: 1 }));
+
+    assert_eq!(get_volume(&app), Some(1));
+    assert_eq!(set_volume(&app, 5), Some(()));
+    assert_eq!(get_volume(&app), Some(5));
+}
+
+#[test]
+fn round_trips_a_state_in_a_mutex() {
+    let app = mock_app();
+    app.manage(Mutex::new(Player { volume: 1 }));
+
+    assert_eq!(get_volume(&app), Some(1));
+    assert_eq!(set_volume(&app, 5), Some(()));
+    assert_eq!(get_volume(&app), Some(5));
+}
+
+#[test]
+fn reads_but_does_not_mutate_a_direct_state() {
+    let app = mock_app();
+    app.manage(Player { volume: 1 });
+
+    assert_eq!(get_volume(&app), Some(1));
+    assert_eq!(set_volume(&app, 5), None);
+    assert_eq!(get_volume(&app), Some(1));
+}
+
+#[test]
+fn uses_the_given_state_access() {
+    let app = mock_app();
+    app.manage(RwLock::new(Locked { volume: 1 }));
+    app.manage(Mutex::new(Locked { volume: 2 }));
+
+    let get = |app: &App<MockRuntime>| {
+        state_helper::acquire::<Locked, locked::FVolume>(
+            app.handle(),
+            Locked::STATE_ACCESS,
+            |locked| locked.volume,
+        )
+    };
+    assert_eq!(get(&app), Some(2));
+    state_helper::acquire_mut(app.handle(), Locked::STATE_ACCESS, |locked: &mut Locked| {
+        *locked::FVolume::value_mut(locked) = 5
+    });
+    assert_eq!(get(&app), Some(5));
+    assert_eq!(
+        app.state::<RwLock<Locked>>()
+            .read()
+            .map(|locked| locked.volume)
+            .ok(),
+        Some(1)
+    );
+}
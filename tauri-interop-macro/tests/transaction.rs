@@ -0,0 +1,64 @@
+//! The field events of a transaction are emitted after its batch
+// the host events are emitted, `_wasm` would generate the listeners instead
+#![cfg(not(feature = "_wasm"))]
+
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+use tauri::test::mock_app;
+use tauri::Listener;
+use tauri_interop::event::{Emit, Field};
+use tauri_interop::{Event, ManagedEmit};
+
+#[derive(Event, ManagedEmit)]
+pub struct Player {
+    title: String,
+    playing: bool,
+}
+
+#[test]
+fn field_listeners_receive_the_events_of_a_transaction() {
+    let app = mock_app();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    for event in [
+        "tauri_interop::batch",
+        player::FTitle::EVENT_NAME,
+        player::FPlaying::EVENT_NAME,
+    ] {
+        let received = received.clone();
+        app.listen(event, move |emitted| {
+            let payload: Value = serde_json::from_str(emitted.payload()).unwrap();
+            received.lock().unwrap().push((event, payload));
+        });
+    }
+
+    let mut player = Player {
+        title: String::new(),
+        playing: false,
+    };
+    player
+        .transaction(app.handle(), |tx| {
+            tx.set::<player::FTitle>("title".into());
+            tx.set::<player::FPlaying>(true);
+            tx.modify::<player::FTitle>(|title| title.push('!'));
+        })
+        .unwrap();
+
+    let received = received.lock().unwrap();
+    let events = received.iter().map(|(event, _)| *event).collect::<Vec<_>>();
+    assert_eq!(
+        events,
+        [
+            "tauri_interop::batch",
+            player::FTitle::EVENT_NAME,
+            player::FPlaying::EVENT_NAME
+        ]
+    );
+
+    // the field events carry the same payload as the batch
+    let batched = received[0].1["events"].as_array().unwrap();
+    assert_eq!(batched[0]["payload"], received[1].1);
+    assert_eq!(batched[1]["payload"], received[2].1);
+    assert_eq!(received[1].1["value"], "title!");
+    assert_eq!(received[2].1["value"], true);
+}
@@ -0,0 +1,7 @@
+// the diagnostics of the host are checked, `_wasm` would generate the bindings instead
+#[cfg(not(feature = "_wasm"))]
+#[test]
+fn ui() {
+    let tests = trybuild::TestCases::new();
+    tests.compile_fail("tests/ui/*.rs");
+}
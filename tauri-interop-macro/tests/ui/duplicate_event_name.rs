@@ -0,0 +1,17 @@
+use tauri_interop::{Event, ManagedEmit};
+
+#[derive(Default, Event, ManagedEmit)]
+pub struct First {
+    #[event(name = "shared")]
+    pub value: u8,
+}
+
+#[derive(Default, Event, ManagedEmit)]
+pub struct Second {
+    #[event(name = "shared")]
+    pub value: u8,
+}
+
+tauri_interop::combine_handlers!(first, second);
+
+fn main() {}
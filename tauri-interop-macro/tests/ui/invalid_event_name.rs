@@ -0,0 +1,9 @@
+use tauri_interop::Event;
+
+#[derive(Default, Event)]
+pub struct Test {
+    #[event(name = "test field")]
+    pub field: u8,
+}
+
+fn main() {}
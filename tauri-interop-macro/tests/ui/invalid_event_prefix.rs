@@ -0,0 +1,9 @@
+use tauri_interop::Event;
+
+#[derive(Default, Event)]
+#[event(prefix = "test.app")]
+pub struct Test {
+    pub field: u8,
+}
+
+fn main() {}
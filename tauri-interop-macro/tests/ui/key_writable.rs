@@ -0,0 +1,11 @@
+use tauri_interop::Event;
+
+#[derive(Default, Event)]
+#[event(key = id)]
+pub struct Test {
+    pub id: u32,
+    #[event(writable)]
+    pub field: u8,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use tauri_interop::Event;
+
+#[derive(Default, Clone, Serialize, Deserialize, Event)]
+#[event(nestable, key = id)]
+pub struct Test {
+    pub id: u32,
+    pub field: u8,
+}
+
+fn main() {}
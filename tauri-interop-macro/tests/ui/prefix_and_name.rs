@@ -0,0 +1,9 @@
+use tauri_interop::Event;
+
+#[derive(Default, Event)]
+#[event(prefix = "app", name = "test")]
+pub struct Test {
+    pub field: u8,
+}
+
+fn main() {}
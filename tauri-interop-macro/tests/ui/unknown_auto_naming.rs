@@ -0,0 +1,9 @@
+use tauri_interop::Event;
+
+#[derive(Default, Event)]
+#[auto_naming(CamelCase)]
+pub struct Test {
+    pub field: u8,
+}
+
+fn main() {}
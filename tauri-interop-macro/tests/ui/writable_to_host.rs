@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use tauri_interop::Event;
+
+#[derive(Default, Clone, Serialize, Deserialize, Event)]
+#[event(direction = "to_host")]
+pub struct Test {
+    #[event(writable)]
+    pub field: u8,
+}
+
+fn main() {}
@@ -2,9 +2,9 @@ tauri_interop::host_usage! {
     // usually u don't need to exclude the crates inside the api,
     // but when the type is removed because it is wrapped in a State,
     // it produced a warning... and we don't like warnings, so we exclude it
-    use crate::model::TestState;
+    use crate::model::{Journal, TestState};
     | use std::sync::RwLock;
-    | use tauri_interop::command::{TauriAppHandle, TauriState};
+    | use tauri_interop::command::{TauriAppHandle, TauriCancellationToken, TauriJob, TauriState};
 }
 
 #[tauri_interop::command]
@@ -18,6 +18,31 @@ pub async fn await_heavy_computing() {
     std::thread::sleep(std::time::Duration::from_millis(5000))
 }
 
+#[tauri_interop::command]
+pub async fn cancellable_heavy_computing(token: TauriCancellationToken) -> Result<u8, String> {
+    for step in 0..50 {
+        if token.is_cancelled() {
+            return Err(format!("cancelled at step {step}"));
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100))
+    }
+
+    Ok(50)
+}
+
+#[tauri_interop::command(job)]
+pub async fn import_job(steps: u8, job: TauriJob<u8>) -> Result<u8, String> {
+    for step in 0..steps {
+        if job.is_cancelled() {
+            return Err(format!("cancelled at step {step}"));
+        }
+        job.progress(step);
+        std::thread::sleep(std::time::Duration::from_millis(100))
+    }
+
+    Ok(steps)
+}
+
 #[tauri_interop::command]
 pub fn greet(name_to_greet: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name_to_greet)
@@ -34,7 +59,7 @@ pub fn invoke_with_return(window: tauri::WebviewWindow) -> String {
         .collect()
 }
 
-#[tauri_interop::command]
+#[tauri_interop::command(cache(ttl = 10))]
 pub fn invoke_with_return_vec() -> Vec<i32> {
     vec![69, 420]
 }
@@ -61,6 +86,44 @@ pub fn emit(state: TauriState<RwLock<TestState>>, handle: TauriAppHandle) {
         .update::<test_mod::FFoo>(&handle, foo_value.into())
         .unwrap();
     state.update::<test_mod::FBar>(&handle, bar_value).unwrap();
+    state.emit::<test_mod::All>(&handle).unwrap();
+}
+
+#[tauri_interop::command]
+pub fn emit_transaction(state: TauriState<RwLock<TestState>>, handle: TauriAppHandle) {
+    use crate::model::test_mod;
+    use tauri_interop::event::Emit;
+
+    let mut state = state.write().unwrap();
+    let bar_value = !state.bar;
+
+    // both fields are received together by the frontend
+    state
+        .transaction(&handle, |tx| {
+            tx.modify::<test_mod::FFoo>(|foo| foo.push('!'));
+            tx.set::<test_mod::FBar>(bar_value);
+        })
+        .unwrap();
+}
+
+#[tauri_interop::command]
+pub fn append_journal(state: TauriState<RwLock<Journal>>, handle: TauriAppHandle, entry: String) {
+    use crate::model::journal;
+    use tauri_interop::event::Emit;
+
+    let mut state = state.write().unwrap();
+    state.push::<journal::FEntries>(&handle, entry).unwrap();
+}
+
+#[tauri_interop::command(cache, invalidate_on(crate::model::TestState => crate::model::test_mod::FBar), leptos)]
+pub fn bar_state(state: TauriState<RwLock<TestState>>) -> bool {
+    state.read().unwrap().bar
+}
+
+/// Invalidated by the deltas of the journal, as appending an entry doesn't emit all entries
+#[tauri_interop::command(cache, invalidate_on(crate::model::Journal => crate::model::journal::FEntries))]
+pub fn journal_length(state: TauriState<RwLock<Journal>>) -> usize {
+    state.read().unwrap().entries.len()
 }
 
 tauri_interop::collect_commands!();
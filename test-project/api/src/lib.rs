@@ -16,5 +16,11 @@ tauri_interop::combine_handlers!(
     model::test_mod,
     // the enum isn't registered as a state in tauri, so registering it wouldn't work anyways
     // model::NamingTestEnumField,
-    model::naming_test_default
+    model::naming_test_default,
+    model::document,
+    model::settings,
+    model::journal,
+    model::paged,
+    model::struct_tuple_state,
+    model::connection_status
 );
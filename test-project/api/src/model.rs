@@ -9,11 +9,14 @@ mod host_impl;
 #[tauri_interop::commands]
 pub mod other_cmd;
 
+use serde::{Deserialize, Serialize};
 use tauri_interop::{Event, ManagedEmit};
 
-#[derive(Default, Event)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Event)]
 #[mod_name(test_mod)]
+#[all_field]
 pub struct TestState {
+    #[event(writable)]
     foo: String,
     pub bar: bool,
 }
@@ -31,18 +34,87 @@ pub struct NamingTestDefault {
     pub bar: bool,
 }
 
+/// Multiple documents are managed side by side, their events are scoped by `id`
+#[derive(Default, Clone, Serialize, Deserialize, Event, ManagedEmit)]
+#[event(key = id)]
+pub struct Document {
+    pub id: u32,
+    pub title: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Event, ManagedEmit)]
+#[event(nestable)]
+pub struct Audio {
+    pub volume: u8,
+    pub muted: bool,
+}
+
+/// Updating `settings::audio::FVolume` only emits the volume, while listeners of
+/// `settings::FAudio` still receive the whole audio settings
+#[derive(Default, Event, ManagedEmit)]
+pub struct Settings {
+    #[event(nested)]
+    pub audio: Audio,
+    pub theme: String,
+}
+
+/// Appending an entry only emits the entry, while listeners of `journal::FEntries` still
+/// receive all entries
+#[derive(Default, Event, ManagedEmit)]
+pub struct Journal {
+    #[event(collection)]
+    pub entries: Vec<String>,
+}
+
+/// A page of items, whose instantiations emit separate events per tag
+#[derive(Default, Clone, Serialize, Deserialize, Event, ManagedEmit)]
+#[event(tag(Paged<String> = "names"), tag(Paged<u32> = "numbers"))]
+pub struct Paged<T> {
+    pub items: Vec<T>,
+    pub page: u32,
+}
+
+#[derive(Default, Clone, Serialize, Deserialize, Event)]
+#[event(direction = "to_host")]
+pub struct UiEvent {
+    pub clicked: u32,
+}
+
+/// Registers the listeners for the events emitted by the frontend
+#[cfg(not(target_family = "wasm"))]
+pub fn listen_ui_events<R: tauri::Runtime>(listener: &impl tauri::Listener<R>) {
+    use tauri_interop::event::HostField;
+
+    ui_event::FClicked::listen(listener, |clicked| log::info!("clicked: {clicked}"));
+}
+
 fn test_naming() {
     test_mod::FBar;
     test_mod::FFoo;
+    test_mod::All;
     NamingTestEnumField::FBar;
     NamingTestEnumField::FFoo;
     naming_test_default::FBar;
     naming_test_default::FFoo;
+    document::FTitle;
+    settings::audio::FVolume;
+    journal::FEntries;
+    struct_tuple_state::F0;
+    connection_status::All;
 }
 
-// /// not allowed
-// #[derive(Default, Event)]
-// pub struct StructTupleState(String);
+#[derive(Default, Event, ManagedEmit)]
+pub struct StructTupleState(String);
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Event, ManagedEmit)]
+pub enum ConnectionStatus {
+    Connected {
+        peer: String,
+    },
+    #[default]
+    Disconnected,
+    Failed(String),
+}
 
 // /// not allowed
 // #[derive(Default, Event)]
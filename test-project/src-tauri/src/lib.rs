@@ -1,4 +1,5 @@
-use api::model::TestState;
+use api::model::{Document, Journal, Paged, Settings, TestState};
+use std::collections::HashMap;
 use std::sync::RwLock;
 use tauri::Manager;
 
@@ -16,6 +17,20 @@ pub fn run() {
             let test_state = RwLock::new(TestState::default());
             app.manage(test_state);
 
+            let document = Document {
+                id: 1,
+                title: "untitled".into(),
+            };
+            let documents = HashMap::from([(document.id.to_string(), document)]);
+            app.manage(RwLock::new(documents));
+
+            app.manage(RwLock::new(Settings::default()));
+            app.manage(RwLock::new(Journal::default()));
+            app.manage(RwLock::new(Paged::<String>::default()));
+            app.manage(RwLock::new(Paged::<u32>::default()));
+
+            api::model::listen_ui_events(app);
+
             Ok(())
         })
         .run(tauri::generate_context!())
@@ -30,6 +30,18 @@ fn main() {
         log::info!("heavy computing finished")
     });
 
+    wasm_bindgen_futures::spawn_local(async {
+        let (greeting, result) = api::batch(|batch| {
+            (
+                batch.call(api::cmd::greet("batch")),
+                batch.call(api::cmd::result_test(false)),
+            )
+        })
+        .await;
+        log::info!("batched greeting: {greeting}");
+        log::info!("batched negativ test: {}", result.expect_err("negativ test"));
+    });
+
     wasm_bindgen_futures::spawn_local(async move {
         let handle_bar = TestState::listen_to::<test_mod::FBar>(|echo| log::info!("bar: {echo}"))
             .await
@@ -43,6 +55,22 @@ fn main() {
         Timeout::new(2000, move || drop(handle_bar)).forget();
     });
 
+    wasm_bindgen_futures::spawn_local(async move {
+        let handle_all =
+            TestState::listen_to::<test_mod::All>(|state| log::info!("state: {state:?}"))
+                .await
+                .unwrap();
+
+        Timeout::new(4000, move || drop(handle_all)).forget();
+    });
+
+    wasm_bindgen_futures::spawn_local(async move {
+        use api::model::UiEvent;
+        use api::event::HostEvent;
+
+        UiEvent { clicked: 1 }.emit_all().await.unwrap();
+    });
+
     Timeout::new(1000, api::cmd::emit).forget();
     Timeout::new(3000, api::cmd::emit).forget();
 
@@ -81,7 +109,15 @@ fn Foo() -> impl IntoView {
     })
     .forget();
 
-    let foo = TestState::use_field::<test_mod::FFoo>(None);
+    let (foo, set_foo) = TestState::use_field_writable::<test_mod::FFoo>(None);
+    // refetched every time bar is emitted
+    let bar = api::cmd::use_bar_state_resource();
 
-    view! { <h1>{foo}</h1> }
+    let reset = move |_| set_foo.set(String::from("reset"));
+
+    view! {
+        <h1>{foo}</h1>
+        <button on:click=reset>Reset foo</button>
+        <p>{move || bar.get().map(|bar| format!("bar: {}", *bar))}</p>
+    }
 }
@@ -0,0 +1,143 @@
+//! The events of a `Transaction` are received once by the subscribers of their field
+#![cfg(all(target_family = "wasm", feature = "event"))]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use tauri_interop::event::{ListenHandle, ListenTarget, Revisioned};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+// replaces tauri's event api with listeners, which are called by `emit`
+#[wasm_bindgen(inline_js = r#"
+const listeners = [];
+
+export function mock_tauri() {
+    globalThis.window = globalThis;
+    globalThis.__TAURI__ ??= {
+        event: {
+            listen: async (event, handler, options) => {
+                const listener = { event, handler, label: options?.target?.label };
+                listeners.push(listener);
+                return () => listeners.splice(listeners.indexOf(listener), 1);
+            },
+        },
+    };
+}
+
+export function listener_count(event) {
+    return listeners.filter((listener) => listener.event === event).length;
+}
+
+export function emit(event, payload, target) {
+    for (const listener of [...listeners]) {
+        const targeted = target === undefined || listener.label === undefined || listener.label === target;
+        if (listener.event === event && targeted) {
+            listener.handler({ event, payload });
+        }
+    }
+}
+
+export function emit_batch(event, payload, target) {
+    emit("tauri_interop::batch", { events: [{ event, payload }] }, target);
+}
+"#)]
+extern "C" {
+    fn mock_tauri();
+    fn listener_count(event: &str) -> usize;
+    fn emit(event: &str, payload: JsValue, target: Option<String>);
+    fn emit_batch(event: &str, payload: JsValue, target: Option<String>);
+}
+
+const BATCH_EVENT: &str = "tauri_interop::batch";
+
+fn received() -> (Rc<RefCell<Vec<u32>>>, impl Fn(u32)) {
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let callback = {
+        let received = received.clone();
+        move |value| received.borrow_mut().push(value)
+    };
+
+    (received, callback)
+}
+
+#[wasm_bindgen_test]
+async fn registered_listener_does_not_listen_to_batches() {
+    mock_tauri();
+    let (received, callback) = received();
+    let handle = ListenHandle::register("batch::registered", callback)
+        .await
+        .unwrap();
+    assert_eq!(listener_count(BATCH_EVENT), 0);
+
+    emit("batch::registered", 1.into(), None);
+    emit_batch("batch::registered", 2.into(), None);
+    assert_eq!(*received.borrow(), [1]);
+
+    drop(handle);
+}
+
+#[wasm_bindgen_test]
+async fn subscriber_receives_batched_events_once() {
+    mock_tauri();
+    let (other_received, other_callback) = received();
+    let (received, callback) = received();
+    let handle = ListenHandle::subscribe("batch::subscribed", callback)
+        .await
+        .unwrap();
+    let other_handle = ListenHandle::subscribe("batch::other", other_callback)
+        .await
+        .unwrap();
+    assert_eq!(listener_count(BATCH_EVENT), 1);
+
+    emit_batch("batch::subscribed", 1.into(), None);
+    assert_eq!(*received.borrow(), [1]);
+    assert!(other_received.borrow().is_empty());
+
+    drop(handle);
+    emit_batch("batch::subscribed", 2.into(), None);
+    assert_eq!(*received.borrow(), [1]);
+
+    // the listener of the batches is detached with the last subscriber
+    drop(other_handle);
+    assert_eq!(listener_count(BATCH_EVENT), 0);
+}
+
+#[wasm_bindgen_test]
+async fn subscriber_receives_batches_of_its_target() {
+    mock_tauri();
+    let (received, callback) = received();
+    let target = ListenTarget::Label("main".into());
+    let handle = ListenHandle::subscribe_with_target("batch::targeted", target, callback)
+        .await
+        .unwrap();
+
+    emit_batch("batch::targeted", 1.into(), Some("other".into()));
+    emit_batch("batch::targeted", 2.into(), Some("main".into()));
+    emit_batch("batch::targeted", 3.into(), None);
+    assert_eq!(*received.borrow(), [2, 3]);
+
+    drop(handle);
+    assert_eq!(listener_count(BATCH_EVENT), 0);
+}
+
+#[wasm_bindgen_test]
+async fn subscriber_skips_field_events_of_received_batches() {
+    mock_tauri();
+    let (received, callback) = received();
+    let handle = ListenHandle::subscribe("batch::echoed", move |update: Revisioned<u32>| {
+        callback(update.value)
+    })
+    .await
+    .unwrap();
+    let revisioned =
+        |revision, value| serde_wasm_bindgen::to_value(&Revisioned { revision, value }).unwrap();
+
+    // a transaction emits the batch followed by the field events
+    emit_batch("batch::echoed", revisioned(1, 1), None);
+    emit("batch::echoed", revisioned(1, 1), None);
+    emit("batch::echoed", revisioned(2, 2), None);
+    assert_eq!(*received.borrow(), [1, 2]);
+
+    drop(handle);
+}
@@ -0,0 +1,60 @@
+//! The deltas of a `CollectionField` are applied to the supported collections
+#![cfg(feature = "event")]
+
+use std::collections::{BTreeMap, HashMap};
+
+use tauri_interop::event::{Collection, Delta};
+
+#[test]
+fn vec_applies_deltas() {
+    let mut vec = vec![1];
+    assert!(vec.apply(Delta::Push(3)));
+    assert!(vec.apply(Delta::Insert(1, 2)));
+    assert_eq!(vec, [1, 2, 3]);
+
+    assert!(vec.apply(Delta::Remove(0)));
+    assert_eq!(vec, [2, 3]);
+
+    assert!(vec.apply(Delta::Clear));
+    assert!(vec.is_empty());
+}
+
+#[test]
+fn vec_rejects_out_of_bounds() {
+    let mut vec = vec![1];
+    assert!(!vec.apply(Delta::Insert(2, 2)));
+    assert!(!vec.apply(Delta::Remove(1)));
+    assert_eq!(vec, [1]);
+}
+
+#[test]
+fn hash_map_applies_deltas() {
+    let mut map = HashMap::from([("a".to_string(), 1)]);
+    assert!(map.apply(Delta::Insert("b".into(), 2)));
+    assert!(map.apply(Delta::Insert("a".into(), 3)));
+    assert_eq!(map, HashMap::from([("a".into(), 3), ("b".into(), 2)]));
+
+    assert!(map.apply(Delta::Remove("a".into())));
+    assert!(!map.apply(Delta::Remove("a".into())));
+    assert_eq!(map, HashMap::from([("b".into(), 2)]));
+
+    assert!(!map.apply(Delta::Push(4)));
+    assert!(map.apply(Delta::Clear));
+    assert!(map.is_empty());
+}
+
+#[test]
+fn btree_map_applies_deltas() {
+    let mut map = BTreeMap::from([(1, 10)]);
+    assert!(map.apply(Delta::Insert(2, 20)));
+    assert!(map.apply(Delta::Insert(1, 30)));
+    assert_eq!(map, BTreeMap::from([(1, 30), (2, 20)]));
+
+    assert!(map.apply(Delta::Remove(1)));
+    assert!(!map.apply(Delta::Remove(1)));
+    assert_eq!(map, BTreeMap::from([(2, 20)]));
+
+    assert!(!map.apply(Delta::Push(40)));
+    assert!(map.apply(Delta::Clear));
+    assert!(map.is_empty());
+}
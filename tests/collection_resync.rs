@@ -0,0 +1,176 @@
+//! A collection, whose subscriber missed a delta, is requested again
+#![cfg(all(target_family = "wasm", feature = "event", feature = "initial_value"))]
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use futures::future::LocalBoxFuture;
+use futures::FutureExt;
+use serde::Serialize;
+use tauri_interop::event::{
+    Delta, EventError, ListenHandle, ListenTarget, Revisioned, RevisionedDelta,
+};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+// replaces tauri's event api with listeners, which are called by `emit`
+#[wasm_bindgen(inline_js = r#"
+const listeners = [];
+
+export function mock_tauri() {
+    globalThis.window = globalThis;
+    globalThis.__TAURI__ ??= {
+        event: {
+            listen: async (event, handler) => {
+                const listener = { event, handler };
+                listeners.push(listener);
+                return () => listeners.splice(listeners.indexOf(listener), 1);
+            },
+        },
+    };
+}
+
+export function emit(event, payload) {
+    for (const listener of [...listeners]) {
+        if (listener.event === event) {
+            listener.handler({ event, payload });
+        }
+    }
+}
+"#)]
+extern "C" {
+    fn mock_tauri();
+    fn emit(event: &str, payload: JsValue);
+}
+
+thread_local! {
+    /// The value returned by `get_value`
+    static REQUESTED: RefCell<Revisioned<Vec<u32>>> = RefCell::default();
+    /// How often `get_value` was called
+    static REQUESTS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn get_value() -> LocalBoxFuture<'static, Result<Revisioned<Vec<u32>>, EventError>> {
+    REQUESTS.set(REQUESTS.get() + 1);
+    let requested = REQUESTED.with_borrow(Clone::clone);
+    async move { Ok(requested) }.boxed_local()
+}
+
+fn send(event: &str, payload: impl Serialize) {
+    emit(event, serde_wasm_bindgen::to_value(&payload).unwrap())
+}
+
+fn push(event: &str, previous: u64, revision: u64, item: u32) {
+    let delta = RevisionedDelta {
+        previous,
+        revision,
+        delta: Delta::<usize, u32>::Push(item),
+    };
+    send(&format!("{event}::delta"), delta)
+}
+
+/// Lets the spawned request of the value complete
+async fn settle() {
+    for _ in 0..4 {
+        let resolved = js_sys::Promise::resolve(&JsValue::NULL);
+        wasm_bindgen_futures::JsFuture::from(resolved)
+            .await
+            .unwrap();
+    }
+}
+
+async fn subscribe(event: &'static str) -> (ListenHandle, Rc<RefCell<Vec<Vec<u32>>>>) {
+    mock_tauri();
+    REQUESTS.set(0);
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let handle = ListenHandle::subscribe_collection(event, ListenTarget::Any, get_value, {
+        let received = received.clone();
+        move |update: Revisioned<Vec<u32>>| received.borrow_mut().push(update.value)
+    })
+    .await
+    .unwrap();
+
+    (handle, received)
+}
+
+#[wasm_bindgen_test]
+async fn applies_deltas_in_order() {
+    let event = "collection::in_order";
+    let (handle, received) = subscribe(event).await;
+
+    send(
+        event,
+        Revisioned {
+            revision: 1,
+            value: vec![1],
+        },
+    );
+    push(event, 1, 2, 2);
+    // already contained in the value
+    push(event, 1, 2, 2);
+    settle().await;
+
+    assert_eq!(*received.borrow(), [vec![1], vec![1, 2]]);
+    assert_eq!(REQUESTS.get(), 0);
+    drop(handle);
+}
+
+#[wasm_bindgen_test]
+async fn requests_value_after_revision_gap() {
+    let event = "collection::gap";
+    let (handle, received) = subscribe(event).await;
+    REQUESTED.set(Revisioned {
+        revision: 3,
+        value: vec![1, 2, 3],
+    });
+
+    send(
+        event,
+        Revisioned {
+            revision: 1,
+            value: vec![1],
+        },
+    );
+    // the delta of revision 2 is missed
+    push(event, 2, 3, 3);
+    settle().await;
+
+    assert_eq!(*received.borrow(), [vec![1], vec![1, 2, 3]]);
+    assert_eq!(REQUESTS.get(), 1);
+    drop(handle);
+}
+
+#[wasm_bindgen_test]
+async fn applies_deltas_received_while_requesting() {
+    let event = "collection::buffered";
+    let (handle, received) = subscribe(event).await;
+    REQUESTED.set(Revisioned {
+        revision: 3,
+        value: vec![1, 2, 3],
+    });
+
+    send(
+        event,
+        Revisioned {
+            revision: 1,
+            value: vec![1],
+        },
+    );
+    push(event, 2, 3, 3);
+    // received out of order, before the value was requested
+    push(event, 4, 5, 5);
+    push(event, 3, 4, 4);
+    settle().await;
+
+    assert_eq!(
+        *received.borrow(),
+        [
+            vec![1],
+            vec![1, 2, 3],
+            vec![1, 2, 3, 4],
+            vec![1, 2, 3, 4, 5]
+        ]
+    );
+    assert_eq!(REQUESTS.get(), 1);
+    drop(handle);
+}
@@ -0,0 +1,24 @@
+//! The keys of instances are encoded into valid event names
+#![cfg(feature = "event")]
+
+use tauri_interop::event::encode_key;
+
+#[test]
+fn keeps_allowed_characters() {
+    assert_eq!(encode_key("document-1/Title"), "document-1/Title");
+}
+
+#[test]
+fn encodes_other_characters() {
+    assert_eq!(encode_key("a b"), "a_20b");
+    assert_eq!(encode_key("ä"), "_c3_a4");
+}
+
+#[test]
+fn encoded_keys_are_distinct() {
+    assert_ne!(encode_key("a_20b"), encode_key("a b"));
+    assert_ne!(
+        encode_key("1::delta"),
+        format!("{}::delta", encode_key("1"))
+    );
+}